@@ -0,0 +1,45 @@
+#![no_main]
+
+// Runs any UTF-8 input that parses. Programs are stopped after a fixed number of statements,
+// since most of what the fuzzer writes loops forever.
+
+use std::io;
+
+use libfuzzer_sys::fuzz_target;
+use rlox::interpreter::{DebugAction, Debugger, Interpreter};
+use rlox::lexer::{Scanner, Token};
+use rlox::parser::{Parser, Stmt};
+
+const MAX_STATEMENTS: usize = 10_000;
+
+struct Budget(usize);
+
+impl Debugger for Budget {
+    fn on_statement(&mut self, _: &Interpreter, _: &Stmt) -> DebugAction {
+        if self.0 == 0 {
+            return DebugAction::Terminate;
+        }
+        self.0 -= 1;
+        return DebugAction::Resume;
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let source: &str = match std::str::from_utf8(data) {
+        Ok(source) => source,
+        Err(_) => return,
+    };
+
+    let mut had_err: bool = false;
+    let tokens: Vec<Token> = Scanner::new(source).scan_tokens(&mut had_err).clone();
+    let statements: Vec<Stmt> = Parser::new(tokens, &mut had_err).parse();
+    if had_err {
+        return;
+    }
+
+    let mut had_runtime_err: bool = false;
+    let mut interpreter: Interpreter = Interpreter::new(&mut had_runtime_err);
+    interpreter.set_output(Box::new(io::sink()));
+    interpreter.set_debugger(Box::new(Budget(MAX_STATEMENTS)));
+    interpreter.interpret(statements);
+});
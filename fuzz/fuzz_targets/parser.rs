@@ -0,0 +1,24 @@
+#![no_main]
+
+// Parses any UTF-8 input into an AST and into a concrete syntax tree, which must give back the
+// input unchanged.
+
+use libfuzzer_sys::fuzz_target;
+use rlox::lexer::{Scanner, Token};
+use rlox::parser::{CstBuilder, Parser};
+
+fuzz_target!(|data: &[u8]| {
+    let source: &str = match std::str::from_utf8(data) {
+        Ok(source) => source,
+        Err(_) => return,
+    };
+
+    let mut had_err: bool = false;
+    let tokens: Vec<Token> = Scanner::new(source).scan_tokens(&mut had_err).clone();
+    Parser::new(tokens, &mut had_err).parse();
+
+    let tokens: Vec<Token> = Scanner::new_lossless(source)
+        .scan_tokens(&mut had_err)
+        .clone();
+    assert_eq!(CstBuilder::new(tokens).build().to_source(), source);
+});
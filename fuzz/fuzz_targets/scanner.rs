@@ -0,0 +1,21 @@
+#![no_main]
+
+// Scans any UTF-8 input, both ways. The lossless tokens must give back the input unchanged.
+
+use libfuzzer_sys::fuzz_target;
+use rlox::lexer::{Scanner, Token};
+
+fuzz_target!(|data: &[u8]| {
+    let source: &str = match std::str::from_utf8(data) {
+        Ok(source) => source,
+        Err(_) => return,
+    };
+
+    let mut had_err: bool = false;
+    Scanner::new(source).scan_tokens(&mut had_err);
+
+    let mut scanner: Scanner = Scanner::new_lossless(source);
+    let tokens: &Vec<Token> = scanner.scan_tokens(&mut had_err);
+    let text: String = tokens.iter().map(|token| token.to_source()).collect();
+    assert_eq!(text, source);
+});
@@ -0,0 +1,180 @@
+use std::cell::{RefCell, RefMut};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+use serde_json::{json, Value as Json};
+
+use super::connection::Connection;
+use super::session::{self, OutputEvents, Session};
+use crate::debugger::Stepper;
+use crate::interpreter::{Interpreter, DEFAULT_STACK_SIZE};
+use crate::lexer::{Scanner, Token};
+use crate::parser::{Parser, Stmt};
+use crate::utils::SyntaxError;
+
+// A program named by `launch`, run once the client is done configuring.
+struct Launch {
+    path: String,
+    source: String,
+    stop_on_entry: bool,
+}
+
+// Speaks the Debug Adapter Protocol over any reader/writer pair, normally stdin and stdout.
+// The session goes: initialize, launch, setBreakpoints, configurationDone, then the program runs
+// and, whenever it pauses, Session answers the requests about it.
+pub struct DebugAdapter {
+    connection: Rc<RefCell<Connection>>,
+    launch: Option<Launch>,
+    // only used to collect breakpoints until the program starts.
+    stepper: Stepper,
+    // of the thread the program runs on, see Interpreter::set_stack_size.
+    stack_size: usize,
+}
+
+impl DebugAdapter {
+    pub fn new(reader: Box<dyn BufRead>, writer: Box<dyn Write>) -> DebugAdapter {
+        return Self {
+            connection: Rc::new(RefCell::new(Connection::new(reader, writer))),
+            launch: None,
+            stepper: Stepper::new(false),
+            stack_size: DEFAULT_STACK_SIZE,
+        };
+    }
+    pub fn set_stack_size(&mut self, size: usize) {
+        self.stack_size = size;
+    }
+
+    // Serves until the client disconnects or closes the input.
+    pub fn run(&mut self) -> io::Result<()> {
+        loop {
+            let request: Json = match self.connection.borrow_mut().read_request()? {
+                Some(request) => request,
+                None => return Ok(()),
+            };
+            let command: &str = request["command"].as_str().unwrap_or_default();
+            let arguments: &Json = &request["arguments"];
+
+            let result: Result<Json, String> = match command {
+                "initialize" => Ok(json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsTerminateRequest": true,
+                })),
+                "launch" => self.launch(arguments),
+                "setBreakpoints" => Ok(session::set_breakpoints(&mut self.stepper, arguments)),
+                "configurationDone" => Ok(Json::Null),
+                "threads" => Ok(session::threads()),
+                "disconnect" | "terminate" => Ok(Json::Null),
+                _ => Err(format!("Request '{}' needs a paused program.", command)),
+            };
+            let launched: bool = result.is_ok() && command == "configurationDone";
+            self.connection.borrow_mut().respond(&request, result)?;
+
+            match command {
+                "initialize" => self
+                    .connection
+                    .borrow_mut()
+                    .event("initialized", json!({}))?,
+                "disconnect" => return Ok(()),
+                _ => (),
+            }
+
+            if launched {
+                if let Some(launch) = self.launch.take() {
+                    self.execute(launch)?;
+                }
+            }
+            if self.connection.borrow().is_closed() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn launch(&mut self, arguments: &Json) -> Result<Json, String> {
+        let path: &str = match arguments["program"].as_str() {
+            Some(path) => path,
+            None => return Err(String::from("Missing 'program' to launch.")),
+        };
+        let source: String = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err) => return Err(format!("Cannot read '{}': {}", path, err)),
+        };
+
+        self.launch = Some(Launch {
+            path: String::from(path),
+            source,
+            stop_on_entry: arguments["stopOnEntry"].as_bool().unwrap_or(false),
+        });
+        return Ok(Json::Null);
+    }
+
+    // Runs the launched program to the end, then reports how it ended.
+    fn execute(&mut self, launch: Launch) -> io::Result<()> {
+        let mut had_err: bool = false;
+        let mut scanner: Scanner = Scanner::new(&launch.source);
+        let tokens: Vec<Token> = scanner.scan_tokens(&mut had_err).clone();
+        let mut parser: Parser = Parser::new(tokens, &mut had_err);
+        let statements: Vec<Stmt> = parser.parse();
+
+        let mut errors: Vec<SyntaxError> = scanner.get_errors().clone();
+        errors.extend(parser.get_errors().iter().cloned());
+
+        let exit_code: i32 = match errors.is_empty() {
+            true => self.interpret(launch, statements),
+            false => {
+                for error in errors {
+                    self.connection.borrow_mut().event(
+                        "output",
+                        json!({
+                            "category": "stderr",
+                            "output": format!("[line {}] Error: {}\n", error.get_line(), error.get_message()),
+                        }),
+                    )?;
+                }
+                65
+            }
+        };
+
+        let mut connection: RefMut<Connection> = self.connection.borrow_mut();
+        if connection.is_closed() {
+            return Ok(());
+        }
+        connection.event("exited", json!({ "exitCode": exit_code }))?;
+        return connection.event("terminated", json!({}));
+    }
+
+    fn interpret(&mut self, launch: Launch, statements: Vec<Stmt>) -> i32 {
+        // Breakpoints set before the launch carry over.
+        let mut stepper: Stepper = std::mem::replace(&mut self.stepper, Stepper::new(false));
+        if launch.stop_on_entry {
+            stepper.step_in();
+        }
+
+        let mut had_runtime_err: bool = false;
+        let mut interpreter: Interpreter = Interpreter::new(&mut had_runtime_err);
+        interpreter.set_file(&launch.path);
+        interpreter.set_stack_size(self.stack_size);
+        interpreter.set_output(Box::new(OutputEvents::new(
+            self.connection.clone(),
+            "stdout",
+        )));
+        interpreter.set_error_output(Box::new(OutputEvents::new(
+            self.connection.clone(),
+            "stderr",
+        )));
+        interpreter.set_debugger(Box::new(Session::new(
+            self.connection.clone(),
+            launch.path,
+            stepper,
+            launch.stop_on_entry,
+        )));
+        interpreter.interpret(statements);
+        // Sends what is left of the output.
+        drop(interpreter);
+
+        return match had_runtime_err {
+            true => 70,
+            false => 0,
+        };
+    }
+}
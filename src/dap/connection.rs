@@ -0,0 +1,81 @@
+use std::io::{self, BufRead, Write};
+
+use serde_json::{json, Value};
+
+use crate::lsp::transport;
+
+// The client end of a debug session. DAP frames its messages like LSP, but they are not JSON-RPC:
+// every message carries its own sequence number and a type.
+pub struct Connection {
+    reader: Box<dyn BufRead>,
+    writer: Box<dyn Write>,
+    // sequence number of the last message sent.
+    seq: u64,
+    // set once the client disconnected: nothing more is read or sent.
+    closed: bool,
+}
+
+impl Connection {
+    pub fn new(reader: Box<dyn BufRead>, writer: Box<dyn Write>) -> Connection {
+        return Self {
+            reader,
+            writer,
+            seq: 0,
+            closed: false,
+        };
+    }
+
+    pub fn is_closed(&self) -> bool {
+        return self.closed;
+    }
+    pub fn close(&mut self) {
+        self.closed = true;
+    }
+
+    // The next request, or None once the client is gone. Requests that are not JSON are answered
+    // with an error response and skipped.
+    pub fn read_request(&mut self) -> io::Result<Option<Value>> {
+        if self.closed {
+            return Ok(None);
+        }
+        loop {
+            let body: Vec<u8> = match transport::read_body(&mut self.reader)? {
+                Some(body) => body,
+                None => return Ok(None),
+            };
+            match serde_json::from_slice::<Value>(&body) {
+                Ok(request) => return Ok(Some(request)),
+                Err(err) => {
+                    // Neither its seq nor its command are known.
+                    let request: Value = json!({ "seq": 0, "command": "" });
+                    self.respond(&request, Err(format!("Cannot parse request: {}", err)))?;
+                }
+            }
+        }
+    }
+
+    pub fn respond(&mut self, request: &Value, result: Result<Value, String>) -> io::Result<()> {
+        let mut response: Value = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = Value::from(message),
+        }
+
+        return self.send(response);
+    }
+
+    pub fn event(&mut self, event: &str, body: Value) -> io::Result<()> {
+        return self.send(json!({ "type": "event", "event": event, "body": body }));
+    }
+
+    fn send(&mut self, mut message: Value) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = Value::from(self.seq);
+        return transport::write_message(&mut self.writer, &message);
+    }
+}
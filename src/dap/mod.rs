@@ -0,0 +1,5 @@
+pub mod adapter;
+mod connection;
+mod session;
+
+pub use adapter::DebugAdapter;
@@ -0,0 +1,322 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use serde_json::{json, Value as Json};
+
+use super::connection::Connection;
+use crate::debugger::{Stepper, StopReason};
+use crate::interpreter::{DebugAction, Debugger, Environment, Frame, Interpreter, Value};
+use crate::lexer::{Scanner, Token};
+use crate::parser::{Expr, Parser, Stmt};
+
+// Lox has a single thread of execution.
+pub const THREAD_ID: u64 = 1;
+
+pub fn threads() -> Json {
+    return json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] });
+}
+
+// What a variablesReference handed to the client stands for, until the program resumes.
+enum Scope {
+    // every block of a function, up to the globals.
+    Locals(Rc<RefCell<Environment>>),
+    Globals,
+}
+
+// The debugger hook of a launched program: pauses where the Stepper says and answers the
+// client's requests until one of them resumes the program.
+pub struct Session {
+    connection: Rc<RefCell<Connection>>,
+    path: String,
+    stepper: Stepper,
+    // whether the next stop is the one asked for by stopOnEntry.
+    on_entry: bool,
+    scopes: Vec<Scope>,
+}
+
+impl Session {
+    pub fn new(
+        connection: Rc<RefCell<Connection>>,
+        path: String,
+        stepper: Stepper,
+        stop_on_entry: bool,
+    ) -> Session {
+        return Self {
+            connection,
+            path,
+            stepper,
+            on_entry: stop_on_entry,
+            scopes: Vec::new(),
+        };
+    }
+
+    fn stopped(
+        &mut self,
+        interpreter: &Interpreter,
+        reason: StopReason,
+    ) -> io::Result<DebugAction> {
+        let reason: &str = match (reason, self.on_entry) {
+            (StopReason::Step, true) => "entry",
+            (StopReason::Step, false) => "step",
+            (StopReason::Breakpoint, _) => "breakpoint",
+        };
+        self.on_entry = false;
+        self.connection.borrow_mut().event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        )?;
+
+        loop {
+            let request: Json = match self.connection.borrow_mut().read_request()? {
+                Some(request) => request,
+                None => return Ok(DebugAction::Terminate),
+            };
+            let command: &str = request["command"].as_str().unwrap_or_default();
+            let arguments: &Json = &request["arguments"];
+
+            let result: Result<Json, String> = match command {
+                "threads" => Ok(threads()),
+                "stackTrace" => Ok(self.stack_trace(interpreter)),
+                "scopes" => self.scopes(interpreter, arguments),
+                "variables" => self.variables(interpreter, arguments),
+                "evaluate" => self.evaluate(interpreter, arguments),
+                "setBreakpoints" => Ok(set_breakpoints(&mut self.stepper, arguments)),
+                "continue" => {
+                    self.stepper.resume();
+                    Ok(json!({ "allThreadsContinued": true }))
+                }
+                "next" => {
+                    self.stepper.step_over(interpreter);
+                    Ok(Json::Null)
+                }
+                "stepIn" => {
+                    self.stepper.step_in();
+                    Ok(Json::Null)
+                }
+                "stepOut" => {
+                    self.stepper.step_out(interpreter);
+                    Ok(Json::Null)
+                }
+                "terminate" | "disconnect" => Ok(Json::Null),
+                _ => Err(format!("Unsupported request '{}'.", command)),
+            };
+            self.connection.borrow_mut().respond(&request, result)?;
+
+            match command {
+                "continue" | "next" | "stepIn" | "stepOut" => {
+                    self.scopes.clear();
+                    return Ok(DebugAction::Resume);
+                }
+                "terminate" => return Ok(DebugAction::Terminate),
+                "disconnect" => {
+                    self.connection.borrow_mut().close();
+                    return Ok(DebugAction::Terminate);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // Frame ids count from the innermost frame, which the client shows first.
+    fn frame(interpreter: &Interpreter, arguments: &Json) -> Result<Frame, String> {
+        let frames: Vec<Frame> = interpreter.get_frames();
+        let id: usize = arguments["frameId"].as_u64().unwrap_or(0) as usize;
+
+        return match frames.len().checked_sub(id + 1) {
+            Some(index) => Ok(frames[index].clone()),
+            None => Err(format!("Unknown frame {}.", id)),
+        };
+    }
+
+    fn stack_trace(&self, interpreter: &Interpreter) -> Json {
+        let frames: Vec<Json> = interpreter
+            .get_frames()
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(id, frame)| {
+                let path: &str = frame.get_file().unwrap_or(&self.path);
+                let name: &str = path.rsplit(['/', '\\']).next().unwrap_or_default();
+                json!({
+                    "id": id,
+                    "name": frame.get_function(),
+                    "source": { "name": name, "path": path },
+                    "line": frame.get_line(),
+                    "column": 1,
+                })
+            })
+            .collect();
+
+        return json!({ "totalFrames": frames.len(), "stackFrames": frames });
+    }
+
+    fn scopes(&mut self, interpreter: &Interpreter, arguments: &Json) -> Result<Json, String> {
+        let frame: Frame = Self::frame(interpreter, arguments)?;
+
+        self.scopes.push(Scope::Locals(frame.get_environment()));
+        let locals: usize = self.scopes.len();
+        self.scopes.push(Scope::Globals);
+        let globals: usize = self.scopes.len();
+
+        return Ok(json!({ "scopes": [
+            { "name": "Locals", "presentationHint": "locals", "variablesReference": locals, "expensive": false },
+            { "name": "Globals", "variablesReference": globals, "expensive": false },
+        ] }));
+    }
+
+    fn variables(&self, interpreter: &Interpreter, arguments: &Json) -> Result<Json, String> {
+        let reference: usize = arguments["variablesReference"].as_u64().unwrap_or(0) as usize;
+        let globals: Rc<RefCell<Environment>> = interpreter.get_globals();
+
+        let values: Vec<(String, Value)> =
+            match reference.checked_sub(1).map(|index| self.scopes.get(index)) {
+                Some(Some(Scope::Locals(environment))) => {
+                    // Inner declarations shadow outer ones.
+                    let mut seen: HashSet<String> = HashSet::new();
+                    let mut values: Vec<(String, Value)> = Vec::new();
+                    let mut scope: Option<Rc<RefCell<Environment>>> = Some(environment.clone());
+
+                    while let Some(environment) = scope {
+                        if Rc::ptr_eq(&environment, &globals) {
+                            break;
+                        }
+                        for (name, value) in environment.borrow().get_values() {
+                            if seen.insert(name.clone()) {
+                                values.push((name, value));
+                            }
+                        }
+                        scope = environment.borrow().get_enclosing();
+                    }
+                    values
+                }
+                Some(Some(Scope::Globals)) => globals.borrow().get_values(),
+                _ => return Err(format!("Unknown variables reference {}.", reference)),
+            };
+
+        let variables: Vec<Json> = values
+            .iter()
+            .map(|(name, value)| {
+                json!({
+                    "name": name,
+                    "value": value.describe(),
+                    "type": value.type_name(),
+                    "variablesReference": 0,
+                })
+            })
+            .collect();
+        return Ok(json!({ "variables": variables }));
+    }
+
+    fn evaluate(&self, interpreter: &Interpreter, arguments: &Json) -> Result<Json, String> {
+        let source: &str = arguments["expression"].as_str().unwrap_or_default();
+        let frame: Frame = Self::frame(interpreter, arguments)?;
+
+        let mut had_err: bool = false;
+        let mut scanner: Scanner = Scanner::new(source);
+        let tokens: Vec<Token> = scanner.scan_tokens(&mut had_err).clone();
+        if let Some(error) = scanner.get_errors().first() {
+            return Err(error.get_message().to_string());
+        }
+        let mut parser: Parser = Parser::new(tokens, &mut had_err);
+        let expr: Option<Expr> = parser.parse_expression();
+        let expr: Expr = match (expr, parser.get_errors().first()) {
+            (Some(expr), None) => expr,
+            (_, Some(error)) => return Err(error.get_message().to_string()),
+            (None, None) => return Err(String::from("Expected expression.")),
+        };
+
+        return match interpreter.evaluate_in(expr, frame.get_environment()) {
+            Ok(value) => Ok(json!({ "result": value.describe(), "variablesReference": 0 })),
+            Err(error) => match error.get_message() {
+                Some(message) => Err(message.to_string()),
+                None => Err(String::from("Evaluation interrupted.")),
+            },
+        };
+    }
+}
+
+impl Debugger for Session {
+    fn on_statement(&mut self, interpreter: &Interpreter, statement: &Stmt) -> DebugAction {
+        let reason: StopReason = match self.stepper.check(interpreter, statement) {
+            Some(reason) => reason,
+            None => return DebugAction::Resume,
+        };
+
+        // A client that cannot be reached cannot resume the program either.
+        return self
+            .stopped(interpreter, reason)
+            .unwrap_or(DebugAction::Terminate);
+    }
+}
+
+// Answers setBreakpoints, which replaces every breakpoint of the (only) source.
+pub fn set_breakpoints(stepper: &mut Stepper, arguments: &Json) -> Json {
+    let lines: Vec<u32> = arguments["breakpoints"]
+        .as_array()
+        .map(|breakpoints| {
+            breakpoints
+                .iter()
+                .filter_map(|breakpoint| breakpoint["line"].as_u64())
+                .map(|line| line as u32)
+                .collect()
+        })
+        .unwrap_or_default();
+    stepper.set_breakpoints(&lines);
+
+    let breakpoints: Vec<Json> = lines
+        .iter()
+        .map(|line| json!({ "verified": true, "line": line }))
+        .collect();
+    return json!({ "breakpoints": breakpoints });
+}
+
+// Interpreter output, sent to the client as `output` events one line at a time.
+pub struct OutputEvents {
+    connection: Rc<RefCell<Connection>>,
+    // "stdout" or "stderr".
+    category: &'static str,
+    pending: Vec<u8>,
+}
+
+impl OutputEvents {
+    pub fn new(connection: Rc<RefCell<Connection>>, category: &'static str) -> OutputEvents {
+        return Self {
+            connection,
+            category,
+            pending: Vec::new(),
+        };
+    }
+
+    fn send(&mut self, end: usize) -> io::Result<()> {
+        let text: Vec<u8> = self.pending.drain(..end).collect();
+        return self.connection.borrow_mut().event(
+            "output",
+            json!({ "category": self.category, "output": String::from_utf8_lossy(&text) }),
+        );
+    }
+}
+
+impl Write for OutputEvents {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        while let Some(newline) = self.pending.iter().position(|byte| *byte == b'\n') {
+            self.send(newline + 1)?;
+        }
+        return Ok(buf.len());
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        return self.send(self.pending.len());
+    }
+}
+
+impl Drop for OutputEvents {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
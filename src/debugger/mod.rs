@@ -0,0 +1,5 @@
+pub mod prompt;
+pub mod stepper;
+
+pub use prompt::PromptDebugger;
+pub use stepper::{Stepper, StopReason};
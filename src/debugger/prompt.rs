@@ -0,0 +1,199 @@
+use std::cell::RefCell;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+
+use super::stepper::Stepper;
+use crate::interpreter::{DebugAction, Debugger, Environment, Frame, Interpreter, RuntimeError};
+use crate::lexer::{Scanner, Token};
+use crate::parser::{Expr, Parser, Stmt};
+
+const HELP: &str = "\
+break, b <line>     stop at the statement starting on <line>
+delete, d <line>    remove the breakpoint on <line>
+step, s             run to the next statement, entering calls
+next, n             run to the next statement in this function or its callers
+continue, c         run to the next breakpoint
+print, p <expr>     evaluate <expr> in the current scope
+locals              list the variables of the enclosing blocks and function
+backtrace, bt       list the active calls, innermost first
+quit, q             stop the program
+An empty line repeats the last step, next or continue.";
+
+// Command line debugger for `rlox debug`: stops before the first statement and then whenever a
+// step finishes or a breakpoint is reached, reading commands until told to resume.
+pub struct PromptDebugger {
+    source: Vec<String>,
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+    stepper: Stepper,
+    // resume command repeated by an empty line.
+    last_command: String,
+}
+
+impl PromptDebugger {
+    pub fn new(source: &str, input: Box<dyn BufRead>, output: Box<dyn Write>) -> PromptDebugger {
+        return Self {
+            source: source.lines().map(String::from).collect(),
+            input,
+            output,
+            stepper: Stepper::new(true),
+            last_command: String::from("step"),
+        };
+    }
+
+    fn show_location(&mut self, interpreter: &Interpreter, line: u32) {
+        let frames: Vec<Frame> = interpreter.get_frames();
+        let function: &str = frames
+            .last()
+            .map(|frame| frame.get_function())
+            .unwrap_or("<script>");
+        let text: &str = self
+            .source
+            .get(line as usize - 1)
+            .map(|text| text.trim())
+            .unwrap_or("");
+
+        let _ = writeln!(self.output, "{} at line {}", function, line);
+        let _ = writeln!(self.output, "{:>5} | {}", line, text);
+    }
+
+    // Reads commands until one resumes or ends the program.
+    fn prompt(&mut self, interpreter: &Interpreter) -> DebugAction {
+        loop {
+            let _ = write!(self.output, "(debug) ");
+            let _ = self.output.flush();
+
+            let mut line: String = String::new();
+            match self.input.read_line(&mut line) {
+                Ok(0) | Err(_) => return DebugAction::Terminate,
+                Ok(_) => (),
+            }
+
+            let mut command: String = String::from(line.trim());
+            if command.is_empty() {
+                command = self.last_command.clone();
+            }
+            let (name, argument): (&str, &str) = match command.split_once(' ') {
+                Some((name, argument)) => (name, argument.trim()),
+                None => (command.as_str(), ""),
+            };
+
+            match name {
+                "step" | "s" => self.stepper.step_in(),
+                "next" | "n" => self.stepper.step_over(interpreter),
+                "continue" | "c" => self.stepper.resume(),
+                "break" | "b" => self.set_breakpoint(argument, true),
+                "delete" | "d" => self.set_breakpoint(argument, false),
+                "print" | "p" => self.print(interpreter, argument),
+                "locals" => self.locals(interpreter),
+                "backtrace" | "bt" => self.backtrace(interpreter),
+                "help" | "h" => {
+                    let _ = writeln!(self.output, "{}", HELP);
+                }
+                "quit" | "q" => return DebugAction::Terminate,
+                _ => {
+                    let _ = writeln!(self.output, "Unknown command '{}', try 'help'.", name);
+                    continue;
+                }
+            }
+
+            if matches!(name, "step" | "s" | "next" | "n" | "continue" | "c") {
+                self.last_command = command.clone();
+                return DebugAction::Resume;
+            }
+        }
+    }
+
+    fn set_breakpoint(&mut self, argument: &str, set: bool) {
+        let line: u32 = match argument.parse::<u32>() {
+            Ok(line) if line > 0 => line,
+            _ => {
+                let _ = writeln!(self.output, "Expected a line number.");
+                return;
+            }
+        };
+
+        let message: String = match set {
+            true => {
+                self.stepper.add_breakpoint(line);
+                format!("Breakpoint at line {}.", line)
+            }
+            false => match self.stepper.remove_breakpoint(line) {
+                true => format!("Deleted breakpoint at line {}.", line),
+                false => format!("No breakpoint at line {}.", line),
+            },
+        };
+        let _ = writeln!(self.output, "{}", message);
+    }
+
+    fn print(&mut self, interpreter: &Interpreter, source: &str) {
+        let mut had_err: bool = false;
+        let mut scanner: Scanner = Scanner::new(source);
+        let tokens: Vec<Token> = scanner.scan_tokens(&mut had_err).clone();
+        if had_err {
+            return;
+        }
+        let mut parser: Parser = Parser::new(tokens, &mut had_err);
+        let expr: Expr = match parser.parse_expression() {
+            Some(expr) => expr,
+            None => return,
+        };
+
+        let text: String = match interpreter.evaluate(expr) {
+            Ok(value) => value.describe(),
+            Err(RuntimeError::TypeError(_, message))
+            | Err(RuntimeError::UndefinedVariable(_, message))
+            | Err(RuntimeError::Native(message)) => message,
+            Err(RuntimeError::Thrown(exception)) => exception.get_message().to_string(),
+            Err(RuntimeError::Return(value)) => value.to_string(),
+            // Statements only, which the prompt does not run.
+            Err(RuntimeError::Break(_)) | Err(RuntimeError::Continue(_)) => return,
+            Err(RuntimeError::Terminated) => return,
+        };
+        let _ = writeln!(self.output, "{}", text);
+    }
+
+    fn locals(&mut self, interpreter: &Interpreter) {
+        let globals: Rc<RefCell<Environment>> = interpreter.get_globals();
+        let mut scope: Option<Rc<RefCell<Environment>>> = Some(interpreter.get_environment());
+        let mut found: bool = false;
+
+        while let Some(environment) = scope {
+            if Rc::ptr_eq(&environment, &globals) {
+                break;
+            }
+            for (name, value) in environment.borrow().get_values() {
+                let _ = writeln!(self.output, "{} = {}", name, value);
+                found = true;
+            }
+            scope = environment.borrow().get_enclosing();
+        }
+
+        if !found {
+            let _ = writeln!(self.output, "No locals.");
+        }
+    }
+
+    fn backtrace(&mut self, interpreter: &Interpreter) {
+        for (index, frame) in interpreter.get_frames().iter().rev().enumerate() {
+            let _ = writeln!(
+                self.output,
+                "#{} {} at line {}",
+                index,
+                frame.get_function(),
+                frame.get_line()
+            );
+        }
+    }
+}
+
+impl Debugger for PromptDebugger {
+    fn on_statement(&mut self, interpreter: &Interpreter, statement: &Stmt) -> DebugAction {
+        if self.stepper.check(interpreter, statement).is_none() {
+            return DebugAction::Resume;
+        }
+
+        self.show_location(interpreter, statement.get_lines().start);
+        return self.prompt(interpreter);
+    }
+}
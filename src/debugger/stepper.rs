@@ -0,0 +1,110 @@
+use std::collections::BTreeSet;
+
+use crate::interpreter::Interpreter;
+use crate::parser::Stmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Step,
+    // stop only at this frame depth or above.
+    Next(usize),
+    Continue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopReason {
+    Step,
+    Breakpoint,
+}
+
+// Where the previous statement was, so a line holding several statements is stopped at once.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Location {
+    line: u32,
+    depth: usize,
+    statement: *const Stmt,
+}
+
+// Decides, statement by statement, where a debugger front end pauses the program: after each
+// step and on breakpoints, which are source lines.
+pub struct Stepper {
+    breakpoints: BTreeSet<u32>,
+    mode: Mode,
+    last_location: Option<Location>,
+}
+
+impl Stepper {
+    // Stops before the first statement if `stop_on_entry`, otherwise runs to a breakpoint.
+    pub fn new(stop_on_entry: bool) -> Stepper {
+        return Self {
+            breakpoints: BTreeSet::new(),
+            mode: match stop_on_entry {
+                true => Mode::Step,
+                false => Mode::Continue,
+            },
+            last_location: None,
+        };
+    }
+
+    pub fn add_breakpoint(&mut self, line: u32) {
+        self.breakpoints.insert(line);
+    }
+    // Whether there was a breakpoint on `line`.
+    pub fn remove_breakpoint(&mut self, line: u32) -> bool {
+        return self.breakpoints.remove(&line);
+    }
+    pub fn set_breakpoints(&mut self, lines: &[u32]) {
+        self.breakpoints = lines.iter().copied().collect();
+    }
+
+    // Resuming commands, to be followed by returning from Debugger::on_statement.
+    pub fn step_in(&mut self) {
+        self.mode = Mode::Step;
+    }
+    pub fn step_over(&mut self, interpreter: &Interpreter) {
+        self.mode = Mode::Next(interpreter.get_frames().len());
+    }
+    pub fn step_out(&mut self, interpreter: &Interpreter) {
+        self.mode = Mode::Next(interpreter.get_frames().len() - 1);
+    }
+    pub fn resume(&mut self) {
+        self.mode = Mode::Continue;
+    }
+
+    // Called from Debugger::on_statement: why the program should pause before `statement`, if
+    // it should.
+    pub fn check(&mut self, interpreter: &Interpreter, statement: &Stmt) -> Option<StopReason> {
+        let location: Location = Location {
+            line: statement.get_lines().start,
+            depth: interpreter.get_frames().len(),
+            statement: statement as *const Stmt,
+        };
+
+        // Loops on a single line come back to the same statement, which is a new stop.
+        let moved: bool = match &self.last_location {
+            Some(last) => {
+                last.line != location.line
+                    || last.depth != location.depth
+                    || last.statement == location.statement
+            }
+            None => true,
+        };
+        self.last_location = Some(location);
+        if !moved {
+            return None;
+        }
+
+        let stepped: bool = match self.mode {
+            Mode::Step => true,
+            Mode::Next(depth) => location.depth <= depth,
+            Mode::Continue => false,
+        };
+        if stepped {
+            return Some(StopReason::Step);
+        }
+        if self.breakpoints.contains(&location.line) {
+            return Some(StopReason::Breakpoint);
+        }
+        return None;
+    }
+}
@@ -0,0 +1,171 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use super::environment::Environment;
+use super::interpreter::{Interpreter, RuntimeError, Value};
+use crate::parser::ast;
+
+// Anything that can appear before "(" in a call.
+pub trait Callable: fmt::Display {
+    fn arity(&self) -> usize;
+    // Name shown in frames and backtraces.
+    fn name(&self) -> String;
+    // File the function was declared in, None for natives and code without a file.
+    fn file(&self) -> Option<String> {
+        return None;
+    }
+    fn call(&self, interpreter: &Interpreter, arguments: Vec<Value>)
+        -> Result<Value, RuntimeError>;
+}
+
+impl fmt::Debug for dyn Callable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self);
+    }
+}
+
+// Functions are only equal to themselves.
+impl PartialEq for dyn Callable {
+    fn eq(&self, other: &Self) -> bool {
+        return std::ptr::eq(
+            self as *const dyn Callable as *const u8,
+            other as *const dyn Callable as *const u8,
+        );
+    }
+}
+
+pub struct LoxFunction {
+    declaration: Rc<ast::Function>,
+    // scope the function was declared in.
+    closure: Rc<RefCell<Environment>>,
+    file: Option<String>,
+}
+
+impl LoxFunction {
+    pub fn new(
+        declaration: Rc<ast::Function>,
+        closure: Rc<RefCell<Environment>>,
+        file: Option<String>,
+    ) -> LoxFunction {
+        return Self {
+            declaration,
+            closure,
+            file,
+        };
+    }
+}
+
+impl Callable for LoxFunction {
+    fn arity(&self) -> usize {
+        return self.declaration.params.len();
+    }
+    fn name(&self) -> String {
+        return self.declaration.name.get_lexeme();
+    }
+    fn file(&self) -> Option<String> {
+        return self.file.clone();
+    }
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let mut environment: Environment = Environment::new_enclosed(self.closure.clone());
+        for (param, argument) in self.declaration.params.iter().zip(arguments) {
+            environment.define(param.get_lexeme(), argument);
+        }
+
+        return match interpreter.execute_block(&self.declaration.body, environment) {
+            Ok(_) => Ok(Value::Nil),
+            Err(RuntimeError::Return(value)) => Ok(value),
+            Err(err) => Err(err),
+        };
+    }
+}
+
+impl fmt::Display for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "<fn {}>", self.name());
+    }
+}
+
+// A function implemented in Rust and defined in the global scope, see natives.
+pub struct NativeFunction {
+    name: &'static str,
+    arity: usize,
+    function: fn(&Interpreter, Vec<Value>) -> Result<Value, RuntimeError>,
+}
+
+impl NativeFunction {
+    pub fn new(
+        name: &'static str,
+        arity: usize,
+        function: fn(&Interpreter, Vec<Value>) -> Result<Value, RuntimeError>,
+    ) -> NativeFunction {
+        return Self {
+            name,
+            arity,
+            function,
+        };
+    }
+}
+
+impl Callable for NativeFunction {
+    fn arity(&self) -> usize {
+        return self.arity;
+    }
+    fn name(&self) -> String {
+        return String::from(self.name);
+    }
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        return (self.function)(interpreter, arguments);
+    }
+}
+
+impl fmt::Display for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "<native fn>");
+    }
+}
+
+// A native method together with the value it was looked up on, e.g. "abc".upper. The method
+// receives that value before the arguments of the call.
+pub struct BoundMethod {
+    receiver: Value,
+    method: NativeFunction,
+}
+
+impl BoundMethod {
+    pub fn new(receiver: Value, method: NativeFunction) -> BoundMethod {
+        return Self { receiver, method };
+    }
+}
+
+impl Callable for BoundMethod {
+    fn arity(&self) -> usize {
+        return self.method.arity();
+    }
+    fn name(&self) -> String {
+        return self.method.name();
+    }
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let mut with_receiver: Vec<Value> = vec![self.receiver.clone()];
+        with_receiver.extend(arguments);
+        return self.method.call(interpreter, with_receiver);
+    }
+}
+
+impl fmt::Display for BoundMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "<native method {}>", self.name());
+    }
+}
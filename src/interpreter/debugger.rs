@@ -0,0 +1,17 @@
+use super::interpreter::Interpreter;
+use crate::parser::Stmt;
+
+// What the program does once the debugger hook returns.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DebugAction {
+    Resume,
+    // Stops the program, as if it had ended.
+    Terminate,
+}
+
+// Hook called by the interpreter before it executes each statement (blocks excepted, their
+// statements are reported one by one). Execution resumes when `on_statement` returns, so a
+// debugger pauses the program simply by not returning until the user says so.
+pub trait Debugger {
+    fn on_statement(&mut self, interpreter: &Interpreter, statement: &Stmt) -> DebugAction;
+}
@@ -0,0 +1,76 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::interpreter::{RuntimeError, Value};
+use crate::lexer::Token;
+
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        return Self::default();
+    }
+    pub fn new_enclosed(enclosing: Rc<RefCell<Environment>>) -> Environment {
+        return Self {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        };
+    }
+
+    pub fn define(&mut self, name: String, value: Value) {
+        self.values.insert(name, value);
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Value, RuntimeError> {
+        if let Some(value) = self.values.get(&name.get_lexeme()) {
+            return Ok(value.clone());
+        }
+        return match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().get(name),
+            None => Err(Self::undefined(name)),
+        };
+    }
+
+    pub fn assign(&mut self, name: &Token, value: Value) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.values.get_mut(&name.get_lexeme()) {
+            *slot = value;
+            return Ok(());
+        }
+        return match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(Self::undefined(name)),
+        };
+    }
+
+    // A variable declared in this scope, not in the ones around it.
+    pub fn get_local(&self, name: &str) -> Option<Value> {
+        return self.values.get(name).cloned();
+    }
+
+    pub fn get_enclosing(&self) -> Option<Rc<RefCell<Environment>>> {
+        return self.enclosing.clone();
+    }
+
+    // Variables declared in this scope only, sorted by name.
+    pub fn get_values(&self) -> Vec<(String, Value)> {
+        let mut values: Vec<(String, Value)> = self
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        values.sort_by(|a, b| a.0.cmp(&b.0));
+        return values;
+    }
+
+    fn undefined(name: &Token) -> RuntimeError {
+        return RuntimeError::UndefinedVariable(
+            name.clone(),
+            format!("Undefined variable '{}'.", name.get_lexeme()),
+        );
+    }
+}
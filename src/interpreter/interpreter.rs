@@ -1,19 +1,106 @@
-use crate::lexer::{Token, TokenType};
-use crate::parser::{ast, Expr, Stmt, Visitor};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+use super::callable::{BoundMethod, Callable, LoxFunction};
+use super::debugger::{DebugAction, Debugger};
+use super::environment::Environment;
+use super::map::{Key, Map};
+use super::module::Module;
+use super::natives;
+use crate::lexer::{Scanner, Token, TokenType};
+use crate::parser::{ast, Expr, Parser, Stmt, Visitor};
 use crate::utils;
 
 pub enum RuntimeError {
     // token , message
     TypeError(Token, String),
+    UndefinedVariable(Token, String),
+    // raised by native functions; Interpreter::call reports it at the call.
+    Native(String),
+    // A value thrown by `throw`, or one of the errors above as an error object once it is caught
+    // or unwinds out of a function.
+    Thrown(Box<Exception>),
+    // Unwinds the interpreter up to the function call, not an error.
+    Return(Value),
+    // Unwind the interpreter up to the loop with the label, or the innermost loop without one.
+    Break(Option<String>),
+    Continue(Option<String>),
+    // Unwinds it completely when the debugger ends the program.
+    Terminated,
+}
+
+impl RuntimeError {
+    // None for the variants that only unwind the interpreter.
+    pub fn get_message(&self) -> Option<&str> {
+        return match self {
+            Self::TypeError(_, message)
+            | Self::UndefinedVariable(_, message)
+            | Self::Native(message) => Some(message),
+            Self::Thrown(exception) => Some(&exception.message),
+            Self::Return(_) | Self::Break(_) | Self::Continue(_) | Self::Terminated => None,
+        };
+    }
+    pub fn get_token(&self) -> Option<&Token> {
+        return match self {
+            Self::TypeError(token, _) | Self::UndefinedVariable(token, _) => Some(token),
+            Self::Thrown(exception) => Some(&exception.token),
+            _ => None,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Exception {
+    // where it was raised.
+    token: Token,
+    // what a catch receives.
+    value: Value,
+    message: String,
+    // the frames active where it was raised, innermost first.
+    trace: Vec<Frame>,
+}
+
+impl Exception {
+    pub fn get_token(&self) -> &Token {
+        return &self.token;
+    }
+    pub fn get_value(&self) -> &Value {
+        return &self.value;
+    }
+    pub fn get_message(&self) -> &str {
+        return &self.message;
+    }
+    pub fn get_trace(&self) -> &Vec<Frame> {
+        return &self.trace;
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum Value {
     Number(f32),
+    Integer(i64),
     String(String),
     Boolean(bool),
+    Callable(Rc<dyn Callable>),
+    // Shared: assigning a list aliases it rather than copying it.
+    List(Rc<RefCell<Vec<Value>>>),
+    Map(Rc<RefCell<Map>>),
+    // start..end, end excluded: "0..=9" is Range(0, 10).
+    Range(i64, i64),
+    // what `import "path" as name` binds name to.
+    Module(Rc<Module>),
     Nil,
 }
+
+// A collection being displayed or compared, to stop at ones that contain themselves.
+type Address = *const ();
+
 impl Value {
     pub fn is_truthy(&self) -> bool {
         match self {
@@ -22,41 +109,1209 @@ impl Value {
             _ => true,
         }
     }
+    pub fn type_name(&self) -> &'static str {
+        return match self {
+            Self::Number(_) => "number",
+            Self::Integer(_) => "integer",
+            Self::String(_) => "string",
+            Self::Boolean(_) => "boolean",
+            Self::Callable(_) => "function",
+            Self::List(_) => "list",
+            Self::Map(_) => "map",
+            Self::Range(..) => "range",
+            Self::Module(_) => "module",
+            Self::Nil => "nil",
+        };
+    }
+    // Like Display, but strings are quoted so they can be told apart from other values.
+    pub fn describe(&self) -> String {
+        return match self {
+            Self::String(string) => format!("{:?}", string),
+            _ => self.to_string(),
+        };
+    }
+    pub fn new_list(elements: Vec<Value>) -> Value {
+        return Self::List(Rc::new(RefCell::new(elements)));
+    }
+
+    pub fn new_map(map: Map) -> Value {
+        return Self::Map(Rc::new(RefCell::new(map)));
+    }
+
+    // Elements are described, a collection inside itself is shown as "[...]" or "{...}".
+    fn display(&self, seen: &mut Vec<Address>) -> String {
+        let (address, cycle): (Address, &str) = match self {
+            Self::List(list) => (Rc::as_ptr(list) as Address, "[...]"),
+            Self::Map(map) => (Rc::as_ptr(map) as Address, "{...}"),
+            _ => return self.describe(),
+        };
+        if seen.contains(&address) {
+            return String::from(cycle);
+        }
+
+        seen.push(address);
+        let text: String = match self {
+            Self::List(list) => {
+                let elements: Vec<String> = list
+                    .borrow()
+                    .iter()
+                    .map(|element| element.display(seen))
+                    .collect();
+                format!("[{}]", elements.join(", "))
+            }
+            Self::Map(map) => {
+                let entries: Vec<String> = map
+                    .borrow()
+                    .get_entries()
+                    .iter()
+                    .map(|(key, value)| {
+                        format!("{}: {}", key.to_value().describe(), value.display(seen))
+                    })
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            _ => self.describe(),
+        };
+        seen.pop();
+        return text;
+    }
+
+    // Collections are equal when their elements are, maps whatever their order. Two collections
+    // already being compared are assumed to be, so comparing ones that contain themselves
+    // terminates.
+    fn equals(&self, other: &Self, seen: &mut Vec<(Address, Address)>) -> bool {
+        return match (self, other) {
+            (Self::Number(l), Self::Number(r)) => l == r,
+            (Self::Integer(l), Self::Integer(r)) => l == r,
+            (Self::Integer(l), Self::Number(r)) | (Self::Number(r), Self::Integer(l)) => {
+                compare_integer(*l, *r) == Some(Ordering::Equal)
+            }
+            (Self::String(l), Self::String(r)) => l == r,
+            (Self::Boolean(l), Self::Boolean(r)) => l == r,
+            (Self::Callable(l), Self::Callable(r)) => l == r,
+            (Self::List(l), Self::List(r)) => {
+                let pair: (Address, Address) = (Rc::as_ptr(l) as Address, Rc::as_ptr(r) as Address);
+                if Rc::ptr_eq(l, r) || seen.contains(&pair) {
+                    return true;
+                }
+                let (l, r) = (l.borrow(), r.borrow());
+                if l.len() != r.len() {
+                    return false;
+                }
+                seen.push(pair);
+                let equal: bool = l.iter().zip(r.iter()).all(|(l, r)| l.equals(r, seen));
+                seen.pop();
+                equal
+            }
+            (Self::Map(l), Self::Map(r)) => {
+                let pair: (Address, Address) = (Rc::as_ptr(l) as Address, Rc::as_ptr(r) as Address);
+                if Rc::ptr_eq(l, r) || seen.contains(&pair) {
+                    return true;
+                }
+                let (l, r) = (l.borrow(), r.borrow());
+                if l.len() != r.len() {
+                    return false;
+                }
+                seen.push(pair);
+                let equal: bool = l.get_entries().iter().all(|(key, l)| match r.get(key) {
+                    Some(r) => l.equals(r, seen),
+                    None => false,
+                });
+                seen.pop();
+                equal
+            }
+            (Self::Range(l_start, l_end), Self::Range(r_start, r_end)) => {
+                l_start == r_start && l_end == r_end
+            }
+            (Self::Module(l), Self::Module(r)) => Rc::ptr_eq(l, r),
+            (Self::Nil, Self::Nil) => true,
+            _ => false,
+        };
+    }
+}
+
+// Position of `index` in a sequence of `len` elements, negative indexes counting from the end.
+pub(crate) fn position(index: i64, len: usize) -> Option<usize> {
+    let position: i64 = if index < 0 { index + len as i64 } else { index };
+    return usize::try_from(position)
+        .ok()
+        .filter(|position| *position < len);
+}
+
+// An integer equals the float it is promoted to, as in arithmetic.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        return self.equals(other, &mut Vec::new());
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            // Unlike integers, whole floats keep their decimal point.
+            Self::Number(number) if number.is_finite() && number.fract() == 0.0 => {
+                write!(f, "{}.0", number)
+            }
+            Self::Number(number) => write!(f, "{}", number),
+            Self::Integer(integer) => write!(f, "{}", integer),
+            Self::String(string) => write!(f, "{}", string),
+            Self::Boolean(boolean) => write!(f, "{}", boolean),
+            Self::Callable(callable) => write!(f, "{}", callable),
+            Self::List(_) | Self::Map(_) => write!(f, "{}", self.display(&mut Vec::new())),
+            Self::Range(start, end) => write!(f, "{}..{}", start, end),
+            Self::Module(module) => write!(f, "<module {}>", module.get_path()),
+            Self::Nil => write!(f, "nil"),
+        };
+    }
+}
+
+// `path` without the "." and the ".." that follow a directory, as shown in errors. Unlike
+// fs::canonicalize, it stays relative and keeps symbolic links.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized: PathBuf = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    return normalized;
+}
+
+// Compares exactly, where converting the integer to a float would round it above 2^24. None
+// when `number` is NaN.
+fn compare_integer(integer: i64, number: f32) -> Option<Ordering> {
+    if number.is_nan() {
+        return None;
+    }
+    // i64::MAX as f32 is 2^63, the first float above every integer.
+    if number >= i64::MAX as f32 {
+        return Some(Ordering::Less);
+    }
+    if number < i64::MIN as f32 {
+        return Some(Ordering::Greater);
+    }
+
+    // In range, a float without its fraction is an integer.
+    let whole: f32 = number.trunc();
+    return Some(
+        integer
+            .cmp(&(whole as i64))
+            .then(0.0.partial_cmp(&(number - whole))?),
+    );
+}
+
+// Address of a local variable: how deep the native stack is at the caller.
+fn stack_position() -> usize {
+    let marker: u8 = 0;
+    return std::hint::black_box(&marker) as *const u8 as usize;
+}
+
+// A function being executed: the script itself at the bottom, then one per active call.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    function: String,
+    // file of the function, None for code without one, such as the prompt's.
+    file: Option<String>,
+    // line and innermost scope of the statement being executed in this function.
+    line: u32,
+    environment: Rc<RefCell<Environment>>,
+}
+
+impl Frame {
+    pub fn get_function(&self) -> &str {
+        return &self.function;
+    }
+    pub fn get_file(&self) -> Option<&str> {
+        return self.file.as_deref();
+    }
+    pub fn get_line(&self) -> u32 {
+        return self.line;
+    }
+    pub fn get_environment(&self) -> Rc<RefCell<Environment>> {
+        return self.environment.clone();
+    }
 }
 
+// Native stack of the thread the interpreter runs on unless told otherwise (see set_stack_size),
+// what Rust gives a spawned thread.
+pub const DEFAULT_STACK_SIZE: usize = 2 * 1024 * 1024;
+
 pub struct Interpreter<'a> {
     had_runtime_err: &'a mut bool,
+    globals: Rc<RefCell<Environment>>,
+    // the native functions alone, around the top-level scope of each module.
+    builtins: Rc<RefCell<Environment>>,
+    // innermost scope of the code being executed.
+    environment: RefCell<Rc<RefCell<Environment>>>,
+    frames: RefCell<Vec<Frame>>,
+    // file of the code being executed, that functions declared by it belong to.
+    file: RefCell<Option<String>>,
+    // errors that reached the top level, in the order they were reported.
+    errors: Vec<Exception>,
+    // imported modules by canonical path, so that each file runs once.
+    modules: RefCell<HashMap<PathBuf, Rc<Module>>>,
+    // canonical and shown paths of the files being run, the importing ones first.
+    importing: RefCell<Vec<(PathBuf, String)>>,
+    // where imports are looked for when they are not next to the importing file.
+    search_paths: Vec<PathBuf>,
+    // taken out while it runs, so statements it evaluates do not call it again.
+    debugger: RefCell<Option<Box<dyn Debugger>>>,
+    // where print writes.
+    output: RefCell<Box<dyn Write>>,
+    // where errors that reach the top level are reported.
+    error_output: RefCell<Box<dyn Write>>,
+    // stack_position() when the interpreter was created.
+    stack_base: usize,
+    // native stack that evaluation may use before calls fail with "Stack overflow.". Each Lox call
+    // is a handful of Rust calls, so runaway recursion would otherwise crash the process.
+    stack_limit: usize,
 }
 
 impl<'a> Interpreter<'a> {
     pub fn new(had_runtime_err: &'a mut bool) -> Interpreter<'a> {
-        return Interpreter { had_runtime_err };
+        let globals: Rc<RefCell<Environment>> = Rc::new(RefCell::new(Environment::new()));
+        let builtins: Rc<RefCell<Environment>> = Rc::new(RefCell::new(Environment::new()));
+        for native in natives::all() {
+            let name: String = native.name();
+            let native: Value = Value::Callable(Rc::new(native));
+            globals.borrow_mut().define(name.clone(), native.clone());
+            builtins.borrow_mut().define(name, native);
+        }
+
+        return Interpreter {
+            had_runtime_err,
+            globals: globals.clone(),
+            builtins,
+            environment: RefCell::new(globals.clone()),
+            frames: RefCell::new(vec![Frame {
+                function: String::from("<script>"),
+                file: None,
+                line: 0,
+                environment: globals,
+            }]),
+            file: RefCell::new(None),
+            errors: Vec::new(),
+            modules: RefCell::new(HashMap::new()),
+            importing: RefCell::new(Vec::new()),
+            search_paths: Vec::new(),
+            debugger: RefCell::new(None),
+            output: RefCell::new(Box::new(io::stdout())),
+            error_output: RefCell::new(Box::new(io::stderr())),
+            stack_base: stack_position(),
+            stack_limit: DEFAULT_STACK_SIZE / 2,
+        };
     }
     pub fn interpret(&mut self, statements: Vec<Stmt>) {
-        for statement in statements {
-            match self.execute(&statement) {
+        // Borrowed, so each statement keeps its address while it runs (see Debugger).
+        for statement in &statements {
+            match self.execute(statement) {
                 Ok(_) => (),
-                Err(err) => match err {
-                    RuntimeError::TypeError(token, msg) => {
-                        utils::runtime_error(&token, &msg, self.had_runtime_err)
+                Err(err) => match self.exception(err) {
+                    RuntimeError::Thrown(exception) => {
+                        let mut error_output = self.error_output.borrow_mut();
+                        utils::runtime_error(
+                            &mut *error_output,
+                            &exception.token,
+                            &exception.message,
+                            self.had_runtime_err,
+                        );
+                        // Raised at the top level, only the script itself is active.
+                        if exception.trace.len() > 1 {
+                            utils::stack_trace(&mut *error_output, &exception.trace);
+                        }
+                        self.errors.push(*exception);
                     }
+                    // Made exceptions.
+                    RuntimeError::TypeError(..) | RuntimeError::UndefinedVariable(..) => (),
+                    // Interpreter::call gives native errors a token.
+                    RuntimeError::Native(_) => (),
+                    // The parser rejects return outside of functions.
+                    RuntimeError::Return(_) => (),
+                    // Neither outside of loops.
+                    RuntimeError::Break(_) | RuntimeError::Continue(_) => (),
+                    RuntimeError::Terminated => return,
                 },
             }
         }
     }
 
+    pub fn had_runtime_error(&self) -> bool {
+        return *self.had_runtime_err;
+    }
+    // Uncaught errors, each with the stack trace reported for it.
+    pub fn get_errors(&self) -> &Vec<Exception> {
+        return &self.errors;
+    }
+
+    // The file the statements to interpret come from, shown in stack traces.
+    pub fn set_file(&mut self, path: &str) {
+        self.file.replace(Some(String::from(path)));
+        if let Some(script) = self.frames.get_mut().first_mut() {
+            script.file = Some(String::from(path));
+        }
+        // So that a module importing the script is a cycle.
+        let canonical: PathBuf = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+        self.importing
+            .replace(vec![(canonical, String::from(path))]);
+    }
+    // Directories to look for imported files in, in order, after the importing file's own.
+    pub fn set_search_paths(&mut self, paths: Vec<PathBuf>) {
+        self.search_paths = paths;
+    }
+    pub fn get_file(&self) -> Option<String> {
+        return self.file.borrow().clone();
+    }
+
+    pub fn set_debugger(&mut self, debugger: Box<dyn Debugger>) {
+        self.debugger.replace(Some(debugger));
+    }
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        self.output.replace(output);
+    }
+    pub fn set_error_output(&mut self, error_output: Box<dyn Write>) {
+        self.error_output.replace(error_output);
+    }
+    // Size of the native stack of the thread the interpreter runs on. Calls may use half of it,
+    // the rest is for what runs between two calls.
+    pub fn set_stack_size(&mut self, size: usize) {
+        self.stack_limit = size / 2;
+    }
+    pub fn get_globals(&self) -> Rc<RefCell<Environment>> {
+        return self.globals.clone();
+    }
+    // Innermost scope of the statement being executed.
+    pub fn get_environment(&self) -> Rc<RefCell<Environment>> {
+        return self.environment.borrow().clone();
+    }
+    // Active calls, innermost last.
+    pub fn get_frames(&self) -> Vec<Frame> {
+        return self.frames.borrow().clone();
+    }
+
     fn execute(&self, statement: &Stmt) -> Result<(), RuntimeError> {
+        if let Some(frame) = self.frames.borrow_mut().last_mut() {
+            frame.line = statement.get_lines().start;
+            frame.environment = self.environment.borrow().clone();
+        }
+
+        if !matches!(statement, Stmt::Block(_)) {
+            let debugger: Option<Box<dyn Debugger>> = self.debugger.borrow_mut().take();
+            if let Some(mut debugger) = debugger {
+                let action: DebugAction = debugger.on_statement(self, statement);
+                self.debugger.replace(Some(debugger));
+
+                if action == DebugAction::Terminate {
+                    return Err(RuntimeError::Terminated);
+                }
+            }
+        }
+
         return statement.accept::<Result<(), RuntimeError>>(self);
     }
 
-    fn evaluate(&self, expr: Expr) -> Result<Value, RuntimeError> {
+    pub(crate) fn execute_block(
+        &self,
+        statements: &Vec<Stmt>,
+        environment: Environment,
+    ) -> Result<(), RuntimeError> {
+        let previous: Rc<RefCell<Environment>> =
+            self.environment.replace(Rc::new(RefCell::new(environment)));
+
+        let mut result: Result<(), RuntimeError> = Ok(());
+        for statement in statements {
+            result = self.execute(statement);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.environment.replace(previous);
+        return result;
+    }
+
+    pub fn evaluate(&self, expr: Expr) -> Result<Value, RuntimeError> {
         return expr.accept::<Result<Value, RuntimeError>>(self);
     }
 
+    // Evaluates `expr` as if it appeared in `environment`, such as the scope of an outer frame.
+    pub fn evaluate_in(
+        &self,
+        expr: Expr,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Value, RuntimeError> {
+        let previous: Rc<RefCell<Environment>> = self.environment.replace(environment);
+        let result: Result<Value, RuntimeError> = self.evaluate(expr);
+        self.environment.replace(previous);
+        return result;
+    }
+
+    fn call(&self, call: &ast::Call) -> Result<Value, RuntimeError> {
+        let callee: Value = self.evaluate(*call.callee.clone())?;
+
+        let mut arguments: Vec<Value> = Vec::new();
+        for argument in &call.arguments {
+            arguments.push(self.evaluate(argument.clone())?);
+        }
+
+        let function: Rc<dyn Callable> = match callee {
+            Value::Callable(function) => function,
+            _ => {
+                return Self::error::<Value>(
+                    call.paren.clone(),
+                    String::from("Can only call functions and classes."),
+                )
+            }
+        };
+        if arguments.len() != function.arity() {
+            return Self::error::<Value>(
+                call.paren.clone(),
+                format!(
+                    "Expected {} arguments but got {}.",
+                    function.arity(),
+                    arguments.len()
+                ),
+            );
+        }
+
+        let line: u32 = call.paren.get_line();
+        return match self.call_in_frame(function, arguments, line) {
+            Err(RuntimeError::Native(message)) => Self::error::<Value>(call.paren.clone(), message),
+            result => result,
+        };
+    }
+
+    // Calls `function` from outside of Lox code, e.g. a native function or the test runner.
+    pub fn call_function(
+        &self,
+        function: Rc<dyn Callable>,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let line: u32 = self.frames.borrow().last().map_or(0, Frame::get_line);
+        return self.call_in_frame(function, arguments, line);
+    }
+
+    fn call_in_frame(
+        &self,
+        function: Rc<dyn Callable>,
+        arguments: Vec<Value>,
+        line: u32,
+    ) -> Result<Value, RuntimeError> {
+        if stack_position().abs_diff(self.stack_base) > self.stack_limit {
+            return Err(RuntimeError::Native(String::from("Stack overflow.")));
+        }
+
+        // A native function runs where it is called.
+        let file: Option<String> = function.file().or_else(|| self.get_file());
+        self.frames.borrow_mut().push(Frame {
+            function: function.name(),
+            file: file.clone(),
+            line,
+            environment: self.environment.borrow().clone(),
+        });
+        let previous: Option<String> = self.file.replace(file);
+        // Raised while the frame is still active, so that it is in the trace.
+        let result: Result<Value, RuntimeError> = function
+            .call(self, arguments)
+            .map_err(|error| self.exception(error));
+        self.file.replace(previous);
+        self.frames.borrow_mut().pop();
+        return result;
+    }
+
+    // Makes runtime errors exceptions, holding an error object with their message and line.
+    fn exception(&self, error: RuntimeError) -> RuntimeError {
+        return match error {
+            RuntimeError::TypeError(token, message)
+            | RuntimeError::UndefinedVariable(token, message) => {
+                let mut object: Map = Map::new();
+                object.insert(
+                    Key::String(String::from("message")),
+                    Value::String(message.clone()),
+                );
+                object.insert(
+                    Key::String(String::from("line")),
+                    Value::Integer(token.get_line() as i64),
+                );
+                self.raise(token, Value::new_map(object), message)
+            }
+            error => error,
+        };
+    }
+
+    fn raise(&self, token: Token, value: Value, message: String) -> RuntimeError {
+        let mut trace: Vec<Frame> = self.frames.borrow().iter().rev().cloned().collect();
+        // The innermost frame is at its statement, the error may be further into it.
+        if let Some(frame) = trace.first_mut() {
+            frame.line = token.get_line();
+        }
+        return RuntimeError::Thrown(Box::new(Exception {
+            token,
+            value,
+            message,
+            trace,
+        }));
+    }
+
+    // The module `path` names, run the first time it is imported.
+    fn import(&self, path: &Token) -> Result<Rc<Module>, RuntimeError> {
+        let file: PathBuf = self.resolve_module(path)?;
+        let canonical: PathBuf = fs::canonicalize(&file).unwrap_or_else(|_| file.clone());
+        if let Some(module) = self.modules.borrow().get(&canonical) {
+            return Ok(module.clone());
+        }
+
+        let name: String = file.display().to_string();
+        let cycle: Option<Vec<String>> = {
+            let importing = self.importing.borrow();
+            importing
+                .iter()
+                .position(|(importing, _)| *importing == canonical)
+                .map(|start| {
+                    importing[start..]
+                        .iter()
+                        .map(|(_, name)| name.clone())
+                        .collect()
+                })
+        };
+        if let Some(mut cycle) = cycle {
+            cycle.push(name);
+            return Self::error::<Rc<Module>>(
+                path.clone(),
+                format!("Import cycle: {}.", cycle.join(" -> ")),
+            );
+        }
+
+        let source: String = match fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(err) => {
+                return Self::error::<Rc<Module>>(
+                    path.clone(),
+                    format!("Cannot read module '{}': {}.", name, err),
+                )
+            }
+        };
+        // The scanner and the parser report the errors themselves.
+        let mut had_err: bool = false;
+        let tokens: Vec<Token> = Scanner::new(&source).scan_tokens(&mut had_err).clone();
+        let statements: Vec<Stmt> = Parser::new(tokens, &mut had_err).parse();
+        if had_err {
+            return Self::error::<Rc<Module>>(
+                path.clone(),
+                format!("Module '{}' has syntax errors.", name),
+            );
+        }
+
+        let environment: Rc<RefCell<Environment>> = Rc::new(RefCell::new(
+            Environment::new_enclosed(self.builtins.clone()),
+        ));
+        self.importing
+            .borrow_mut()
+            .push((canonical.clone(), name.clone()));
+        self.frames.borrow_mut().push(Frame {
+            function: String::from("<module>"),
+            file: Some(name.clone()),
+            line: 0,
+            environment: environment.clone(),
+        });
+        let previous_file: Option<String> = self.file.replace(Some(name.clone()));
+        let previous: Rc<RefCell<Environment>> = self.environment.replace(environment.clone());
+
+        let mut result: Result<(), RuntimeError> = Ok(());
+        for statement in &statements {
+            result = self.execute(statement);
+            if result.is_err() {
+                break;
+            }
+        }
+        let result: Result<(), RuntimeError> = result.map_err(|error| self.exception(error));
+
+        self.environment.replace(previous);
+        self.file.replace(previous_file);
+        self.frames.borrow_mut().pop();
+        self.importing.borrow_mut().pop();
+        result?;
+
+        let module: Rc<Module> = Rc::new(Module::new(name, environment));
+        self.modules.borrow_mut().insert(canonical, module.clone());
+        return Ok(module);
+    }
+
+    // Relative to the directory of the importing file, then to each search path in turn.
+    fn resolve_module(&self, path: &Token) -> Result<PathBuf, RuntimeError> {
+        let relative: String = match path.get_token_type() {
+            TokenType::String(relative) => relative.clone(),
+            _ => path.get_lexeme(),
+        };
+        let directory: PathBuf = match self.get_file() {
+            Some(file) => Path::new(&file)
+                .parent()
+                .map_or_else(PathBuf::new, Path::to_path_buf),
+            None => PathBuf::new(),
+        };
+
+        for directory in std::iter::once(&directory).chain(self.search_paths.iter()) {
+            let file: PathBuf = directory.join(&relative);
+            if file.is_file() {
+                return Ok(normalize(&file));
+            }
+        }
+        return Self::error::<PathBuf>(path.clone(), format!("Cannot find module '{}'.", relative));
+    }
+
+    // Runs the catch when the body raises an exception, then the finally however they ended.
+    fn execute_try(&self, try_stmt: &ast::Try) -> Result<(), RuntimeError> {
+        let mut result: Result<(), RuntimeError> =
+            self.execute_block(&try_stmt.body.statements, self.new_scope());
+
+        if let Some((name, catch)) = &try_stmt.catch {
+            result = match result.map_err(|error| self.exception(error)) {
+                Err(RuntimeError::Thrown(exception)) => {
+                    let mut environment: Environment = self.new_scope();
+                    environment.define(name.get_lexeme(), exception.value);
+                    self.execute_block(&catch.statements, environment)
+                }
+                result => result,
+            };
+        }
+        if let Some(finally) = &try_stmt.finally {
+            // Skipped when the debugger ends the program. An error or a jump out of it replaces
+            // how the rest ended.
+            if !matches!(result, Err(RuntimeError::Terminated)) {
+                self.execute_block(&finally.statements, self.new_scope())?;
+            }
+        }
+        return result;
+    }
+
+    fn export(module: &Module, name: &Token) -> Result<Value, RuntimeError> {
+        return match module.get(&name.get_lexeme()) {
+            Some(value) => Ok(value),
+            None => Self::error::<Value>(
+                name.clone(),
+                format!(
+                    "Undefined name '{}' in module '{}'.",
+                    name.get_lexeme(),
+                    module.get_path()
+                ),
+            ),
+        };
+    }
+
+    fn new_scope(&self) -> Environment {
+        return Environment::new_enclosed(self.environment.borrow().clone());
+    }
+
+    // Runs the body once per element of the iterable, each time in a new scope where the loop
+    // variable holds the element.
+    fn execute_for_in(&self, for_in: &ast::ForIn) -> Result<(), RuntimeError> {
+        let iterable: Value = self.evaluate(*for_in.iterable.clone())?;
+        // false once the loop is broken out of.
+        let run = |element: Value| -> Result<bool, RuntimeError> {
+            let mut environment: Environment =
+                Environment::new_enclosed(self.environment.borrow().clone());
+            environment.define(for_in.name.get_lexeme(), element);
+
+            let previous: Rc<RefCell<Environment>> =
+                self.environment.replace(Rc::new(RefCell::new(environment)));
+            let result: Result<(), RuntimeError> = self.execute(&for_in.body);
+            self.environment.replace(previous);
+            return Self::loop_continues(result, &for_in.label);
+        };
+
+        match &iterable {
+            // Read as the loop goes, so elements the body appends are visited too.
+            Value::List(list) => {
+                let mut index: usize = 0;
+                loop {
+                    let element: Option<Value> = list.borrow().get(index).cloned();
+                    let goes_on: bool = match element {
+                        Some(element) => run(element)?,
+                        None => false,
+                    };
+                    if !goes_on {
+                        break;
+                    }
+                    index += 1;
+                }
+            }
+            Value::Map(map) => match Self::method(map, "iterator") {
+                Some(iterator) => {
+                    let iterator: Value = self.call_method(&for_in.name, iterator)?;
+                    let (has_next, next) = match &iterator {
+                        Value::Map(iterator) => (
+                            Self::method(iterator, "has_next"),
+                            Self::method(iterator, "next"),
+                        ),
+                        _ => (None, None),
+                    };
+                    let (has_next, next) = match (has_next, next) {
+                        (Some(has_next), Some(next)) => (has_next, next),
+                        _ => {
+                            return Self::error::<()>(
+                                for_in.name.clone(),
+                                String::from(
+                                    "An iterator must have 'has_next' and 'next' methods.",
+                                ),
+                            )
+                        }
+                    };
+                    while self
+                        .call_method(&for_in.name, has_next.clone())?
+                        .is_truthy()
+                    {
+                        if !run(self.call_method(&for_in.name, next.clone())?)? {
+                            break;
+                        }
+                    }
+                }
+                // The keys, as they were when the loop started.
+                None => {
+                    let keys: Vec<Value> = map
+                        .borrow()
+                        .get_entries()
+                        .iter()
+                        .map(|(key, _)| key.to_value())
+                        .collect();
+                    for key in keys {
+                        if !run(key)? {
+                            break;
+                        }
+                    }
+                }
+            },
+            Value::String(string) => {
+                for char in string.chars() {
+                    if !run(Value::String(char.to_string()))? {
+                        break;
+                    }
+                }
+            }
+            Value::Range(start, end) => {
+                for integer in *start..*end {
+                    if !run(Value::Integer(integer))? {
+                        break;
+                    }
+                }
+            }
+            _ => {
+                return Self::error::<()>(
+                    for_in.name.clone(),
+                    format!("Cannot iterate over {}.", iterable.describe()),
+                )
+            }
+        }
+
+        return Ok(());
+    }
+
+    // A user object's method: a function without parameters in one of its entries.
+    fn method(object: &Rc<RefCell<Map>>, name: &str) -> Option<Rc<dyn Callable>> {
+        return match object.borrow().get(&Key::String(String::from(name))) {
+            Some(Value::Callable(function)) if function.arity() == 0 => Some(function.clone()),
+            _ => None,
+        };
+    }
+
+    // Calls a method on behalf of the interpreter, reporting native errors at `token`.
+    fn call_method(&self, token: &Token, method: Rc<dyn Callable>) -> Result<Value, RuntimeError> {
+        return match self.call_function(method, Vec::new()) {
+            Err(RuntimeError::Native(message)) => Self::error::<Value>(token.clone(), message),
+            result => result,
+        };
+    }
+
+    // Runs the loop in its own scope, where the initializer declares its variable.
+    fn execute_for(&self, for_stmt: &ast::For) -> Result<(), RuntimeError> {
+        if let Some(initializer) = &for_stmt.initializer {
+            self.execute(initializer)?;
+        }
+
+        loop {
+            if let Some(condition) = &for_stmt.condition {
+                if !self.evaluate(*condition.clone())?.is_truthy() {
+                    break;
+                }
+            }
+            // The increment runs after a continue too.
+            if !Self::loop_continues(self.execute(&for_stmt.body), &for_stmt.label)? {
+                break;
+            }
+            if let Some(increment) = &for_stmt.increment {
+                self.evaluate(*increment.clone())?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Whether a loop goes on after running its body, which the break or continue aimed at the
+    // loop with `label` stops unwinding.
+    fn loop_continues(
+        result: Result<(), RuntimeError>,
+        label: &Option<Token>,
+    ) -> Result<bool, RuntimeError> {
+        return match result {
+            Ok(()) => Ok(true),
+            Err(RuntimeError::Break(target)) if Self::is_target(&target, label) => Ok(false),
+            Err(RuntimeError::Continue(target)) if Self::is_target(&target, label) => Ok(true),
+            Err(err) => Err(err),
+        };
+    }
+
+    // Without a label, a jump targets the innermost loop.
+    fn is_target(target: &Option<String>, label: &Option<Token>) -> bool {
+        return match (target, label) {
+            (None, _) => true,
+            (Some(target), Some(label)) => *target == label.get_lexeme(),
+            (Some(_), None) => false,
+        };
+    }
+
     fn is_equal(l: Value, r: Value) -> bool {
         return l == r;
     }
 
+    fn binary_operation(
+        operator: &Token,
+        left: Value,
+        right: Value,
+    ) -> Result<Value, RuntimeError> {
+        let value: Value = match (left.clone(), right.clone()) {
+            (Value::Integer(l), Value::Integer(r)) => Self::integer_operation(operator, l, r)?,
+            (Value::Number(l), Value::Number(r)) => Self::number_operation(operator, l, r)?,
+            // Compared exactly. For arithmetic, an integer mixed with a float is promoted to one.
+            (Value::Integer(l), Value::Number(r)) => {
+                match Self::comparison(operator, compare_integer(l, r)) {
+                    Some(value) => value,
+                    None => Self::number_operation(operator, l as f32, r)?,
+                }
+            }
+            (Value::Number(l), Value::Integer(r)) => {
+                match Self::comparison(operator, compare_integer(r, l).map(Ordering::reverse)) {
+                    Some(value) => value,
+                    None => Self::number_operation(operator, l, r as f32)?,
+                }
+            }
+            // "+" makes a new list, leaving both operands as they were.
+            (Value::List(l), Value::List(r)) if operator.get_token_type() == &TokenType::Plus => {
+                let mut elements: Vec<Value> = l.borrow().clone();
+                elements.extend(r.borrow().iter().cloned());
+
+                Value::new_list(elements)
+            }
+            (Value::String(l), Value::String(r)) => match operator.get_token_type() {
+                TokenType::Plus if l.len() + r.len() > natives::MAX_STRING_LENGTH => {
+                    return Self::error::<Value>(
+                        operator.clone(),
+                        String::from("Concatenated string is too long."),
+                    )
+                }
+                TokenType::Plus => {
+                    let mut concated_str: String = l.clone();
+                    concated_str.push_str(&r);
+
+                    Value::String(concated_str)
+                }
+                TokenType::BangEqual => Value::Boolean(!Self::is_equal(left, right)),
+                TokenType::EqualEqual => Value::Boolean(Self::is_equal(left, right)),
+                // Lexicographic, by code point.
+                TokenType::Greater => Value::Boolean(l > r),
+                TokenType::GreaterEqual => Value::Boolean(l >= r),
+                TokenType::Less => Value::Boolean(l < r),
+                TokenType::LessEqual => Value::Boolean(l <= r),
+                _ => {
+                    return Self::error::<Value>(
+                        operator.clone(),
+                        String::from("Operator cannot be applied on two strings"),
+                    )
+                }
+            },
+            _ => match operator.get_token_type() {
+                TokenType::BangEqual => Value::Boolean(!Self::is_equal(left, right)),
+                TokenType::EqualEqual => Value::Boolean(Self::is_equal(left, right)),
+                TokenType::Plus => {
+                    return Self::error::<Value>(
+                        operator.clone(),
+                        String::from("Operands must be two number or two strings."),
+                    )
+                }
+                token_type if Self::is_bitwise(token_type) => {
+                    return Self::error::<Value>(
+                        operator.clone(),
+                        String::from("Operands must be integers."),
+                    )
+                }
+                _ => {
+                    return Self::error::<Value>(
+                        operator.clone(),
+                        String::from("Operands must be a number."),
+                    )
+                }
+            },
+        };
+        return Ok(value);
+    }
+
+    // The result of `operator` for operands ordered as `ordering`, None if it is not a comparison.
+    // Operands without an order, as with NaN, are only unequal.
+    fn comparison(operator: &Token, ordering: Option<Ordering>) -> Option<Value> {
+        let result: bool = match operator.get_token_type() {
+            TokenType::EqualEqual => ordering == Some(Ordering::Equal),
+            TokenType::BangEqual => ordering != Some(Ordering::Equal),
+            TokenType::Greater => ordering == Some(Ordering::Greater),
+            TokenType::GreaterEqual => {
+                matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+            }
+            TokenType::Less => ordering == Some(Ordering::Less),
+            TokenType::LessEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            _ => return None,
+        };
+        return Some(Value::Boolean(result));
+    }
+
+    // The binary operator a compound assignment applies, None for "=". It keeps the lexeme and
+    // position of the assignment operator, where its errors are reported.
+    fn compound_operator(operator: &Token) -> Option<Token> {
+        let token_type: TokenType = match operator.get_token_type() {
+            TokenType::PlusEqual => TokenType::Plus,
+            TokenType::MinusEqual => TokenType::Minus,
+            TokenType::StarEqual => TokenType::Star,
+            TokenType::SlashEqual => TokenType::Slash,
+            TokenType::PercentEqual => TokenType::Percent,
+            _ => return None,
+        };
+        return Some(Token::new(
+            token_type,
+            operator.get_lexeme(),
+            operator.get_line(),
+            operator.get_column(),
+        ));
+    }
+
+    fn is_bitwise(token_type: &TokenType) -> bool {
+        return matches!(
+            token_type,
+            TokenType::Ampersand
+                | TokenType::Pipe
+                | TokenType::Caret
+                | TokenType::LessLess
+                | TokenType::GreaterGreater
+        );
+    }
+
+    fn number_operation(operator: &Token, l: f32, r: f32) -> Result<Value, RuntimeError> {
+        let value: Value = match operator.get_token_type() {
+            TokenType::Minus => Value::Number(l - r),
+            TokenType::Slash => Value::Number(l / r),
+            TokenType::Star => Value::Number(l * r),
+            TokenType::Plus => Value::Number(l + r),
+            TokenType::Percent => Value::Number(l % r),
+            TokenType::StarStar => Value::Number(l.powf(r)),
+
+            TokenType::Greater => Value::Boolean(l > r),
+            TokenType::GreaterEqual => Value::Boolean(l >= r),
+            TokenType::Less => Value::Boolean(l < r),
+            TokenType::LessEqual => Value::Boolean(l <= r),
+
+            TokenType::BangEqual => Value::Boolean(l != r),
+            TokenType::EqualEqual => Value::Boolean(l == r),
+
+            token_type if Self::is_bitwise(token_type) => {
+                return Self::error::<Value>(
+                    operator.clone(),
+                    String::from("Operands must be integers."),
+                )
+            }
+            _ => {
+                return Self::error::<Value>(
+                    operator.clone(),
+                    String::from("Operator cannot be applied on two numbers"),
+                )
+            }
+        };
+        return Ok(value);
+    }
+
+    // Integer arithmetic is exact: results that do not fit in 64 bits are errors rather than
+    // wrapping around, and division truncates towards zero. A negative exponent makes a float.
+    fn integer_operation(operator: &Token, l: i64, r: i64) -> Result<Value, RuntimeError> {
+        let result: Option<i64> = match operator.get_token_type() {
+            TokenType::Minus => l.checked_sub(r),
+            TokenType::Star => l.checked_mul(r),
+            TokenType::Plus => l.checked_add(r),
+            TokenType::Slash => {
+                if r == 0 {
+                    return Self::error::<Value>(
+                        operator.clone(),
+                        String::from("Division by zero."),
+                    );
+                }
+                l.checked_div(r)
+            }
+            TokenType::Percent => {
+                if r == 0 {
+                    return Self::error::<Value>(
+                        operator.clone(),
+                        String::from("Division by zero."),
+                    );
+                }
+                l.checked_rem(r)
+            }
+            TokenType::StarStar => match u32::try_from(r) {
+                Ok(exponent) => l.checked_pow(exponent),
+                Err(_) if r < 0 => return Ok(Value::Number((l as f32).powf(r as f32))),
+                Err(_) => None,
+            },
+
+            TokenType::Ampersand => Some(l & r),
+            TokenType::Pipe => Some(l | r),
+            TokenType::Caret => Some(l ^ r),
+            // Bits shifted out are lost, ">>" keeps the sign.
+            TokenType::LessLess | TokenType::GreaterGreater => {
+                let shift: u32 = match u32::try_from(r) {
+                    Ok(shift) if shift < i64::BITS => shift,
+                    _ => {
+                        return Self::error::<Value>(
+                            operator.clone(),
+                            String::from("Shift amount must be between 0 and 63."),
+                        )
+                    }
+                };
+                match operator.get_token_type() {
+                    TokenType::LessLess => Some(l << shift),
+                    _ => Some(l >> shift),
+                }
+            }
+
+            TokenType::Greater => return Ok(Value::Boolean(l > r)),
+            TokenType::GreaterEqual => return Ok(Value::Boolean(l >= r)),
+            TokenType::Less => return Ok(Value::Boolean(l < r)),
+            TokenType::LessEqual => return Ok(Value::Boolean(l <= r)),
+
+            TokenType::BangEqual => return Ok(Value::Boolean(l != r)),
+            TokenType::EqualEqual => return Ok(Value::Boolean(l == r)),
+
+            _ => {
+                return Self::error::<Value>(
+                    operator.clone(),
+                    String::from("Operator cannot be applied on two numbers"),
+                )
+            }
+        };
+
+        return match result {
+            Some(integer) => Ok(Value::Integer(integer)),
+            None => Self::error::<Value>(operator.clone(), String::from("Integer overflow.")),
+        };
+    }
+
+    // object[index], for lists and maps.
+    fn get_index(bracket: &Token, object: &Value, index: &Value) -> Result<Value, RuntimeError> {
+        return match object {
+            Value::List(list) => {
+                let list = list.borrow();
+                Ok(list[Self::sequence_position(bracket, "List", index, list.len())?].clone())
+            }
+            Value::Map(map) => match map.borrow().get(&Self::map_key(bracket, index)?) {
+                Some(value) => Ok(value.clone()),
+                None => Self::error::<Value>(
+                    bracket.clone(),
+                    format!("Undefined key {}.", index.describe()),
+                ),
+            },
+            // The character at index, as a string.
+            Value::String(string) => {
+                let len: usize = string.chars().count();
+                let position: usize = Self::sequence_position(bracket, "String", index, len)?;
+                Ok(Value::String(
+                    string.chars().nth(position).into_iter().collect(),
+                ))
+            }
+            _ => Self::error::<Value>(
+                bracket.clone(),
+                String::from("Only lists, maps and strings can be indexed."),
+            ),
+        };
+    }
+
+    // object[index] = value. Assigning to a key that is not in a map adds it.
+    fn set_index(
+        bracket: &Token,
+        object: &Value,
+        index: &Value,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        match object {
+            Value::List(list) => {
+                let len: usize = list.borrow().len();
+                let position: usize = Self::sequence_position(bracket, "List", index, len)?;
+                list.borrow_mut()[position] = value;
+            }
+            Value::Map(map) => {
+                let key: Key = Self::map_key(bracket, index)?;
+                map.borrow_mut().insert(key, value);
+            }
+            Value::String(_) => {
+                return Self::error::<()>(
+                    bracket.clone(),
+                    String::from("Strings cannot be modified."),
+                )
+            }
+            _ => {
+                return Self::error::<()>(
+                    bracket.clone(),
+                    String::from("Only lists, maps and strings can be indexed."),
+                )
+            }
+        }
+        return Ok(());
+    }
+
+    fn map_key(token: &Token, value: &Value) -> Result<Key, RuntimeError> {
+        return Key::from_value(value)
+            .or_else(|message| Self::error::<Key>(token.clone(), message));
+    }
+
+    // Checks that `index` is an integer within a `sequence` ("List" or "String") of `len`
+    // elements.
+    fn sequence_position(
+        bracket: &Token,
+        sequence: &str,
+        index: &Value,
+        len: usize,
+    ) -> Result<usize, RuntimeError> {
+        let index: i64 = match index {
+            Value::Integer(index) => *index,
+            _ => {
+                return Self::error::<usize>(
+                    bracket.clone(),
+                    format!("{} index must be an integer.", sequence),
+                )
+            }
+        };
+        return match position(index, len) {
+            Some(position) => Ok(position),
+            None => Self::error::<usize>(
+                bracket.clone(),
+                format!(
+                    "Index {} is out of bounds for a {} of length {}.",
+                    index,
+                    sequence.to_lowercase(),
+                    len
+                ),
+            ),
+        };
+    }
+
     fn error<T>(token: Token, msg: String) -> Result<T, RuntimeError> {
         return Err(RuntimeError::TypeError(token, msg));
     }
@@ -66,8 +1321,9 @@ impl<'a> Visitor<Expr, Result<Value, RuntimeError>> for Interpreter<'a> {
     fn visit(&self, production: &Expr) -> Result<Value, RuntimeError> {
         let value: Value = match production {
             Expr::Literal(literal) => match literal {
-                ast::Literal::Number(number) => Value::Number(*number),
-                ast::Literal::String(string) => Value::String(string.to_string()),
+                ast::Literal::Number { value, .. } => Value::Number(*value),
+                ast::Literal::Integer { value, .. } => Value::Integer(*value),
+                ast::Literal::String { value, .. } => Value::String(value.to_string()),
                 ast::Literal::True => Value::Boolean(true),
                 ast::Literal::False => Value::Boolean(false),
                 ast::Literal::Nil => Value::Nil,
@@ -75,93 +1331,196 @@ impl<'a> Visitor<Expr, Result<Value, RuntimeError>> for Interpreter<'a> {
 
             Expr::Grouping(grouping) => self.evaluate(*grouping.expression.clone())?,
 
-            Expr::Unary(unary) => {
-                let right: Value = self.evaluate(*unary.right.clone())?;
+            Expr::Variable(variable) => self.environment.borrow().borrow().get(&variable.name)?,
 
-                match unary.operator.get_token_type() {
-                    TokenType::Minus => match right {
-                        Value::Number(number) => Value::Number(-number),
-                        _ => {
-                            return Self::error::<Value>(
-                                unary.operator.clone(),
-                                String::from("Operand must be a number."),
-                            )
-                        }
-                    },
-                    TokenType::Bang => Value::Boolean(!right.is_truthy()),
-                    _ => {
-                        return Self::error::<Value>(
-                            unary.operator.clone(),
-                            String::from("Operator is not unary."),
-                        )
+            Expr::Assign(assign) => {
+                // "x += y" is "x = x + y": x is read before y is evaluated.
+                let value: Value = match Self::compound_operator(&assign.operator) {
+                    Some(operator) => {
+                        let current: Value =
+                            self.environment.borrow().borrow().get(&assign.name)?;
+                        let operand: Value = self.evaluate(*assign.value.clone())?;
+                        Self::binary_operation(&operator, current, operand)?
                     }
+                    None => self.evaluate(*assign.value.clone())?,
+                };
+                self.environment
+                    .borrow()
+                    .borrow_mut()
+                    .assign(&assign.name, value.clone())?;
+                value
+            }
+
+            Expr::Logical(logical) => {
+                let left: Value = self.evaluate(*logical.left.clone())?;
+
+                // The right operand is only evaluated when the left one does not decide.
+                match (logical.operator.get_token_type(), left.is_truthy()) {
+                    (TokenType::Or, true) | (TokenType::And, false) => left,
+                    _ => self.evaluate(*logical.right.clone())?,
                 }
             }
 
-            Expr::Binary(binary) => {
-                let left: Value = self.evaluate(*binary.left.clone())?;
-                let right: Value = self.evaluate(*binary.right.clone())?;
+            Expr::Conditional(conditional) => {
+                match self.evaluate(*conditional.condition.clone())?.is_truthy() {
+                    true => self.evaluate(*conditional.then_branch.clone())?,
+                    false => self.evaluate(*conditional.else_branch.clone())?,
+                }
+            }
 
-                match (left.clone(), right.clone()) {
-                    (Value::Number(l), Value::Number(r)) => {
-                        match binary.operator.get_token_type() {
-                            TokenType::Minus => Value::Number(l - r),
-                            TokenType::Slash => Value::Number(l / r),
-                            TokenType::Star => Value::Number(l * r),
-                            TokenType::Plus => Value::Number(l + r),
+            Expr::Comma(comma) => {
+                self.evaluate(*comma.left.clone())?;
+                self.evaluate(*comma.right.clone())?
+            }
 
-                            TokenType::Greater => Value::Boolean(l > r),
-                            TokenType::GreaterEqual => Value::Boolean(l >= r),
-                            TokenType::Less => Value::Boolean(l < r),
-                            TokenType::LessEqual => Value::Boolean(l <= r),
+            Expr::Call(call) => self.call(call)?,
 
-                            TokenType::BangEqual => Value::Boolean(!Self::is_equal(left, right)),
-                            TokenType::EqualEqual => Value::Boolean(Self::is_equal(left, right)),
+            Expr::List(list) => {
+                let mut elements: Vec<Value> = Vec::new();
+                for element in &list.elements {
+                    elements.push(self.evaluate(element.clone())?);
+                }
+                Value::new_list(elements)
+            }
 
-                            _ => {
+            // Keys and values are evaluated in order, a repeated key keeps its first place.
+            Expr::Map(map) => {
+                let mut entries: Map = Map::new();
+                for (key, value) in &map.entries {
+                    let key: Value = self.evaluate(key.clone())?;
+                    let value: Value = self.evaluate(value.clone())?;
+                    entries.insert(Self::map_key(&map.brace, &key)?, value);
+                }
+                Value::new_map(entries)
+            }
+
+            Expr::Range(range) => {
+                let start: Value = self.evaluate(*range.start.clone())?;
+                let end: Value = self.evaluate(*range.end.clone())?;
+
+                match (start, end) {
+                    (Value::Integer(start), Value::Integer(end)) => {
+                        let end: Option<i64> = match range.operator.get_token_type() {
+                            TokenType::DotDotEqual => end.checked_add(1),
+                            _ => Some(end),
+                        };
+                        match end {
+                            Some(end) => Value::Range(start, end),
+                            None => {
                                 return Self::error::<Value>(
-                                    binary.operator.clone(),
-                                    String::from("Operator cannot be applied on two numbers"),
+                                    range.operator.clone(),
+                                    String::from("Integer overflow."),
                                 )
                             }
                         }
                     }
-                    (Value::String(l), Value::String(r)) => {
-                        match binary.operator.get_token_type() {
-                            TokenType::Plus => {
-                                let mut concated_str: String = l.clone();
-                                concated_str.push_str(&r);
+                    _ => {
+                        return Self::error::<Value>(
+                            range.operator.clone(),
+                            String::from("Range bounds must be integers."),
+                        )
+                    }
+                }
+            }
 
-                                Value::String(concated_str)
-                            }
-                            TokenType::BangEqual => Value::Boolean(!Self::is_equal(left, right)),
-                            TokenType::EqualEqual => Value::Boolean(Self::is_equal(left, right)),
-                            _ => {
+            // Only strings have methods, bound to the string they are looked up on.
+            Expr::Get(get) => {
+                let object: Value = self.evaluate(*get.object.clone())?;
+                let name: String = get.name.get_lexeme();
+
+                match (&object, natives::string_method(&name)) {
+                    (Value::String(_), Some(method)) => {
+                        Value::Callable(Rc::new(BoundMethod::new(object, method)))
+                    }
+                    (Value::Module(module), _) => Self::export(module, &get.name)?,
+                    _ => {
+                        return Self::error::<Value>(
+                            get.name.clone(),
+                            format!("Undefined method '{}' on {}.", name, object.type_name()),
+                        )
+                    }
+                }
+            }
+
+            Expr::Index(index) => {
+                let object: Value = self.evaluate(*index.object.clone())?;
+                let key: Value = self.evaluate(*index.index.clone())?;
+
+                Self::get_index(&index.bracket, &object, &key)?
+            }
+
+            Expr::SetIndex(set_index) => {
+                let object: Value = self.evaluate(*set_index.object.clone())?;
+                let key: Value = self.evaluate(*set_index.index.clone())?;
+                // As for variables, "xs[i] += y" is "xs[i] = xs[i] + y".
+                let value: Value = match Self::compound_operator(&set_index.operator) {
+                    Some(operator) => {
+                        let current: Value = Self::get_index(&set_index.bracket, &object, &key)?;
+                        let operand: Value = self.evaluate(*set_index.value.clone())?;
+                        Self::binary_operation(&operator, current, operand)?
+                    }
+                    None => self.evaluate(*set_index.value.clone())?,
+                };
+                Self::set_index(&set_index.bracket, &object, &key, value.clone())?;
+                value
+            }
+
+            // Embedded values are stringified the way print shows them.
+            Expr::Interpolation(interpolation) => {
+                let mut string: String = String::new();
+                for part in &interpolation.parts {
+                    string.push_str(&self.evaluate(part.clone())?.to_string());
+                }
+                Value::String(string)
+            }
+
+            Expr::Unary(unary) => {
+                let right: Value = self.evaluate(*unary.right.clone())?;
+
+                match unary.operator.get_token_type() {
+                    TokenType::Minus => match right {
+                        Value::Number(number) => Value::Number(-number),
+                        Value::Integer(integer) => match integer.checked_neg() {
+                            Some(negated) => Value::Integer(negated),
+                            None => {
                                 return Self::error::<Value>(
-                                    binary.operator.clone(),
-                                    String::from("Operator cannot be applied on two strings"),
+                                    unary.operator.clone(),
+                                    String::from("Integer overflow."),
                                 )
                             }
-                        }
-                    }
-                    _ => match binary.operator.get_token_type() {
-                        TokenType::BangEqual => Value::Boolean(!Self::is_equal(left, right)),
-                        TokenType::EqualEqual => Value::Boolean(Self::is_equal(left, right)),
-                        TokenType::Plus => {
+                        },
+                        _ => {
                             return Self::error::<Value>(
-                                binary.operator.clone(),
-                                String::from("Operands must be two number or two strings."),
+                                unary.operator.clone(),
+                                String::from("Operand must be a number."),
                             )
                         }
+                    },
+                    TokenType::Tilde => match right {
+                        Value::Integer(integer) => Value::Integer(!integer),
                         _ => {
                             return Self::error::<Value>(
-                                binary.operator.clone(),
-                                String::from("Operands must be a number."),
+                                unary.operator.clone(),
+                                String::from("Operand must be an integer."),
                             )
                         }
                     },
+                    TokenType::Bang => Value::Boolean(!right.is_truthy()),
+                    _ => {
+                        return Self::error::<Value>(
+                            unary.operator.clone(),
+                            String::from("Operator is not unary."),
+                        )
+                    }
                 }
             }
+
+            Expr::Binary(binary) => {
+                let left: Value = self.evaluate(*binary.left.clone())?;
+                let right: Value = self.evaluate(*binary.right.clone())?;
+
+                Self::binary_operation(&binary.operator, left, right)?
+            }
         };
 
         return Ok(value);
@@ -176,31 +1535,103 @@ impl<'a> Visitor<Stmt, Result<(), RuntimeError>> for Interpreter<'a> {
             }
             Stmt::Print(print) => {
                 let value: Value = self.evaluate(*print.expression.clone())?;
-                println!("{:?}", value);
+                let _ = writeln!(self.output.borrow_mut(), "{}", value);
+            }
+            Stmt::Var(var) => {
+                let value: Value = match &var.initializer {
+                    Some(initializer) => self.evaluate(*initializer.clone())?,
+                    None => Value::Nil,
+                };
+                self.environment
+                    .borrow()
+                    .borrow_mut()
+                    .define(var.name.get_lexeme(), value);
+            }
+            Stmt::Block(block) => {
+                let enclosing: Rc<RefCell<Environment>> = self.environment.borrow().clone();
+                self.execute_block(&block.statements, Environment::new_enclosed(enclosing))?;
+            }
+            Stmt::If(if_stmt) => {
+                if self.evaluate(*if_stmt.condition.clone())?.is_truthy() {
+                    self.execute(&if_stmt.then_branch)?;
+                } else if let Some(else_branch) = &if_stmt.else_branch {
+                    self.execute(else_branch)?;
+                }
+            }
+            Stmt::While(while_stmt) => {
+                while self.evaluate(*while_stmt.condition.clone())?.is_truthy() {
+                    if !Self::loop_continues(self.execute(&while_stmt.body), &while_stmt.label)? {
+                        break;
+                    }
+                }
+            }
+            Stmt::For(for_stmt) => {
+                let enclosing: Rc<RefCell<Environment>> = self.environment.borrow().clone();
+                let previous: Rc<RefCell<Environment>> = self
+                    .environment
+                    .replace(Rc::new(RefCell::new(Environment::new_enclosed(enclosing))));
+
+                let result: Result<(), RuntimeError> = self.execute_for(for_stmt);
+                self.environment.replace(previous);
+                result?;
             }
+            Stmt::ForIn(for_in) => self.execute_for_in(for_in)?,
+            Stmt::Function(function) => {
+                let closure: Rc<RefCell<Environment>> = self.environment.borrow().clone();
+                let callable: LoxFunction =
+                    LoxFunction::new(Rc::new(function.clone()), closure, self.get_file());
+                self.environment.borrow().borrow_mut().define(
+                    function.name.get_lexeme(),
+                    Value::Callable(Rc::new(callable)),
+                );
+            }
+            Stmt::Return(return_stmt) => {
+                let value: Value = match &return_stmt.value {
+                    Some(value) => self.evaluate(*value.clone())?,
+                    None => Value::Nil,
+                };
+                return Err(RuntimeError::Return(value));
+            }
+            Stmt::Import(import) => {
+                let module: Rc<Module> = self.import(&import.path)?;
+                let environment: Rc<RefCell<Environment>> = self.environment.borrow().clone();
+
+                if let Some(alias) = &import.alias {
+                    environment
+                        .borrow_mut()
+                        .define(alias.get_lexeme(), Value::Module(module.clone()));
+                }
+                for name in &import.names {
+                    let value: Value = Self::export(&module, name)?;
+                    environment.borrow_mut().define(name.get_lexeme(), value);
+                }
+            }
+            Stmt::Throw(throw) => {
+                let value: Value = self.evaluate(*throw.value.clone())?;
+                let message: String = match &value {
+                    // An error object, like the ones runtime errors make.
+                    Value::Map(map) => {
+                        match map.borrow().get(&Key::String(String::from("message"))) {
+                            Some(Value::String(message)) => message.clone(),
+                            _ => value.to_string(),
+                        }
+                    }
+                    _ => value.to_string(),
+                };
+                return Err(self.raise(throw.keyword.clone(), value, message));
+            }
+            Stmt::Try(try_stmt) => self.execute_try(try_stmt)?,
+            Stmt::Break(break_stmt) => {
+                let label: Option<String> = break_stmt.label.as_ref().map(Token::get_lexeme);
+                return Err(RuntimeError::Break(label));
+            }
+            Stmt::Continue(continue_stmt) => {
+                let label: Option<String> = continue_stmt.label.as_ref().map(Token::get_lexeme);
+                return Err(RuntimeError::Continue(label));
+            }
+            // Run by the test runner, never as part of the program.
+            Stmt::Test(_) => (),
         };
         return Ok(());
     }
 }
-
-//pub enum Expr {
-//    Literal : enum {
-//        Number(f32),
-//        String(String),
-//        True,
-//        False,
-//        Nil
-//    },
-//    Grouping : struct {
-//        pub expression: Box<Expr>,
-//    },
-//    Unary : struct {
-//        pub operator: Token,
-//        pub right: Box<Expr>,
-//    },
-//    Binary : struct {
-//        pub left: Box<Expr>,
-//        pub right: Box<Expr>,
-//        pub operator: Token,
-//    },
-//}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use super::interpreter::Value;
+
+// A value that can be a map key. Floats with an integer value are stored as that integer, so
+// that keys equal as values are the same key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    Integer(i64),
+    // bits of the float, NaN being a single key.
+    Number(u32),
+    String(String),
+    Boolean(bool),
+    Nil,
+}
+
+impl Key {
+    // Err with the message to report for values that cannot be keys.
+    pub fn from_value(value: &Value) -> Result<Key, String> {
+        return match value {
+            Value::Integer(integer) => Ok(Self::Integer(*integer)),
+            Value::Number(number)
+                if number.fract() == 0.0
+                    && *number >= i64::MIN as f32
+                    && *number < i64::MAX as f32 =>
+            {
+                Ok(Self::Integer(*number as i64))
+            }
+            Value::Number(number) if number.is_nan() => Ok(Self::Number(f32::NAN.to_bits())),
+            Value::Number(number) => Ok(Self::Number(number.to_bits())),
+            Value::String(string) => Ok(Self::String(string.clone())),
+            Value::Boolean(boolean) => Ok(Self::Boolean(*boolean)),
+            Value::Nil => Ok(Self::Nil),
+            _ => Err(format!("Cannot use a {} as a map key.", value.type_name())),
+        };
+    }
+    pub fn to_value(&self) -> Value {
+        return match self {
+            Self::Integer(integer) => Value::Integer(*integer),
+            Self::Number(bits) => Value::Number(f32::from_bits(*bits)),
+            Self::String(string) => Value::String(string.clone()),
+            Self::Boolean(boolean) => Value::Boolean(*boolean),
+            Self::Nil => Value::Nil,
+        };
+    }
+}
+
+// Hash map that keeps its entries in insertion order, so iterating over it is deterministic.
+#[derive(Debug, Clone, Default)]
+pub struct Map {
+    entries: Vec<(Key, Value)>,
+    // index of every key in entries.
+    positions: HashMap<Key, usize>,
+}
+
+impl Map {
+    pub fn new() -> Map {
+        return Self::default();
+    }
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+    pub fn get(&self, key: &Key) -> Option<&Value> {
+        return self
+            .positions
+            .get(key)
+            .map(|position| &self.entries[*position].1);
+    }
+    pub fn contains(&self, key: &Key) -> bool {
+        return self.positions.contains_key(key);
+    }
+    // A key already in the map keeps its place.
+    pub fn insert(&mut self, key: Key, value: Value) {
+        match self.positions.get(&key) {
+            Some(position) => self.entries[*position].1 = value,
+            None => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+    pub fn remove(&mut self, key: &Key) -> Option<Value> {
+        let position: usize = self.positions.remove(key)?;
+        let (_, value) = self.entries.remove(position);
+        for (key, _) in &self.entries[position..] {
+            if let Some(later) = self.positions.get_mut(key) {
+                *later -= 1;
+            }
+        }
+        return Some(value);
+    }
+    // In insertion order.
+    pub fn get_entries(&self) -> &Vec<(Key, Value)> {
+        return &self.entries;
+    }
+}
@@ -1,3 +1,14 @@
+pub mod callable;
+pub mod debugger;
+pub mod environment;
 pub mod interpreter;
+pub mod map;
+pub mod module;
+pub mod natives;
 
-pub use interpreter::Interpreter;
+pub use callable::{BoundMethod, Callable, LoxFunction, NativeFunction};
+pub use debugger::{DebugAction, Debugger};
+pub use environment::Environment;
+pub use interpreter::{Exception, Frame, Interpreter, RuntimeError, Value, DEFAULT_STACK_SIZE};
+pub use map::{Key, Map};
+pub use module::Module;
@@ -0,0 +1,28 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::environment::Environment;
+use super::interpreter::Value;
+
+// A file run by `import`. Its top-level declarations are its exports, each module having its
+// own, so two modules can declare the same name.
+#[derive(Debug)]
+pub struct Module {
+    // as resolved from the first import of it.
+    path: String,
+    // top-level scope of the module, enclosed by the native functions only.
+    environment: Rc<RefCell<Environment>>,
+}
+
+impl Module {
+    pub fn new(path: String, environment: Rc<RefCell<Environment>>) -> Module {
+        return Self { path, environment };
+    }
+    pub fn get_path(&self) -> &str {
+        return &self.path;
+    }
+    // The current value of an export, which the module's functions may still change.
+    pub fn get(&self, name: &str) -> Option<Value> {
+        return self.environment.borrow().get_local(name);
+    }
+}
@@ -0,0 +1,446 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use super::callable::{Callable, NativeFunction};
+use super::interpreter::{self, Interpreter, RuntimeError, Value};
+use super::map::{Key, Map};
+
+static START: OnceLock<Instant> = OnceLock::new();
+
+// Longest string, in bytes, that repeat(), replace() and "+" build. Without a limit a large
+// result aborts the process when the allocation fails.
+pub const MAX_STRING_LENGTH: usize = 256 * 1024 * 1024;
+
+// Every native function, defined in the globals of each interpreter.
+pub fn all() -> Vec<NativeFunction> {
+    START.get_or_init(Instant::now);
+
+    return vec![
+        NativeFunction::new("clock", 0, clock),
+        NativeFunction::new("int", 1, int),
+        NativeFunction::new("float", 1, float),
+        NativeFunction::new("assert", 1, assert),
+        NativeFunction::new("assert_eq", 2, assert_eq),
+        NativeFunction::new("assert_error", 2, assert_error),
+        NativeFunction::new("len", 1, len),
+        NativeFunction::new("push", 2, push),
+        NativeFunction::new("pop", 1, pop),
+        NativeFunction::new("insert", 3, insert),
+        NativeFunction::new("remove", 2, remove),
+        NativeFunction::new("slice", 3, slice),
+        NativeFunction::new("keys", 1, keys),
+        NativeFunction::new("values", 1, values),
+        NativeFunction::new("has", 2, has),
+    ];
+}
+
+// Methods of strings, e.g. "abc".upper(). Each receives the string before its arguments, which
+// the arity does not count.
+pub fn string_method(name: &str) -> Option<NativeFunction> {
+    let methods: Vec<NativeFunction> = vec![
+        NativeFunction::new("len", 0, len),
+        NativeFunction::new("slice", 2, slice),
+        NativeFunction::new("upper", 0, upper),
+        NativeFunction::new("lower", 0, lower),
+        NativeFunction::new("trim", 0, trim),
+        NativeFunction::new("split", 1, split),
+        NativeFunction::new("join", 1, join),
+        NativeFunction::new("find", 1, find),
+        NativeFunction::new("replace", 2, replace),
+        NativeFunction::new("starts_with", 1, starts_with),
+        NativeFunction::new("ends_with", 1, ends_with),
+        NativeFunction::new("repeat", 1, repeat),
+    ];
+    return methods.into_iter().find(|method| method.name() == name);
+}
+
+// clock() -> seconds since the first interpreter was created. Floats are f32, too coarse to hold
+// seconds since the Unix epoch.
+fn clock(_: &Interpreter, _: Vec<Value>) -> Result<Value, RuntimeError> {
+    let start: &Instant = START.get_or_init(Instant::now);
+    return Ok(Value::Number(start.elapsed().as_secs_f32()));
+}
+
+// int(value) -> value as an integer. Floats are truncated towards zero, strings are parsed.
+fn int(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let integer: Option<i64> = match &arguments[0] {
+        Value::Integer(integer) => Some(*integer),
+        // `as` would saturate, so floats out of range (NaN included) are refused.
+        Value::Number(number) if *number >= i64::MIN as f32 && *number < i64::MAX as f32 => {
+            Some(*number as i64)
+        }
+        Value::String(string) => string.trim().parse::<i64>().ok(),
+        _ => None,
+    };
+
+    return match integer {
+        Some(integer) => Ok(Value::Integer(integer)),
+        None => fail(format!(
+            "Cannot convert {} to an integer.",
+            arguments[0].describe()
+        )),
+    };
+}
+
+// float(value) -> value as a float. Strings are parsed.
+fn float(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let number: Option<f32> = match &arguments[0] {
+        Value::Number(number) => Some(*number),
+        Value::Integer(integer) => Some(*integer as f32),
+        Value::String(string) => string.trim().parse::<f32>().ok(),
+        _ => None,
+    };
+
+    return match number {
+        Some(number) => Ok(Value::Number(number)),
+        None => fail(format!(
+            "Cannot convert {} to a float.",
+            arguments[0].describe()
+        )),
+    };
+}
+
+// assert(condition) fails unless condition is truthy.
+fn assert(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    if !arguments[0].is_truthy() {
+        return fail(String::from("Assertion failed."));
+    }
+    return Ok(Value::Nil);
+}
+
+// assert_eq(actual, expected) fails unless both are equal.
+fn assert_eq(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    if arguments[0] != arguments[1] {
+        return fail(format!(
+            "Expected {} but got {}.",
+            arguments[1].describe(),
+            arguments[0].describe()
+        ));
+    }
+    return Ok(Value::Nil);
+}
+
+// assert_error(function, message) calls function without arguments and fails unless it raises
+// a runtime error with that message.
+fn assert_error(interpreter: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let function = match &arguments[0] {
+        Value::Callable(function) if function.arity() == 0 => function.clone(),
+        _ => return fail(String::from("Expect a function without parameters.")),
+    };
+    let expected: &str = match &arguments[1] {
+        Value::String(message) => message,
+        _ => return fail(String::from("Expect an error message.")),
+    };
+
+    return match interpreter.call_function(function, Vec::new()) {
+        Ok(_) => fail(format!(
+            "Expected error '{}' but none was raised.",
+            expected
+        )),
+        Err(error) => match error.get_message() {
+            Some(message) if message == expected => Ok(Value::Nil),
+            Some(message) => fail(format!(
+                "Expected error '{}' but got '{}'.",
+                expected, message
+            )),
+            // The debugger ended the program.
+            None => Err(error),
+        },
+    };
+}
+
+// len(value) -> number of elements of a list or range, of entries of a map, or of characters of
+// a string.
+fn len(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let len: usize = match &arguments[0] {
+        Value::List(list) => list.borrow().len(),
+        Value::Map(map) => map.borrow().len(),
+        Value::Range(start, end) => (*end as i128 - *start as i128).max(0) as usize,
+        Value::String(string) => string.chars().count(),
+        value => return fail(format!("Cannot take the length of {}.", value.describe())),
+    };
+    return Ok(Value::Integer(len as i64));
+}
+
+// push(list, value) appends value to list.
+fn push(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let list: Rc<RefCell<Vec<Value>>> = expect_list(&arguments[0])?;
+    list.borrow_mut().push(arguments[1].clone());
+    return Ok(Value::Nil);
+}
+
+// pop(list) -> last element of list, removed from it.
+fn pop(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let list: Rc<RefCell<Vec<Value>>> = expect_list(&arguments[0])?;
+    let last: Option<Value> = list.borrow_mut().pop();
+    return match last {
+        Some(value) => Ok(value),
+        None => fail(String::from("Cannot pop from an empty list.")),
+    };
+}
+
+// insert(list, index, value) inserts value before the element at index, or appends it when
+// index is len(list).
+fn insert(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let list: Rc<RefCell<Vec<Value>>> = expect_list(&arguments[0])?;
+    let len: usize = list.borrow().len();
+    let position: usize = match &arguments[1] {
+        Value::Integer(index) if *index == len as i64 => len,
+        index => expect_position(index, len)?,
+    };
+    list.borrow_mut().insert(position, arguments[2].clone());
+    return Ok(Value::Nil);
+}
+
+// remove(list, index) -> element at index, removed from list.
+// remove(map, key) -> value of key, removed from map.
+fn remove(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    if let Value::Map(map) = &arguments[0] {
+        let key: Key = expect_key(&arguments[1])?;
+        let removed: Option<Value> = map.borrow_mut().remove(&key);
+        return match removed {
+            Some(value) => Ok(value),
+            None => fail(format!("Undefined key {}.", arguments[1].describe())),
+        };
+    }
+    let list: Rc<RefCell<Vec<Value>>> = expect_list(&arguments[0])?;
+    let len: usize = list.borrow().len();
+    let position: usize = expect_position(&arguments[1], len)?;
+    let removed: Value = list.borrow_mut().remove(position);
+    return Ok(removed);
+}
+
+// slice(list, start, end) -> new list of the elements from start up to, not including, end.
+// slice(string, start, end) -> the characters of string between the same bounds.
+// Negative bounds count from the end, and bounds past either end are clamped to it.
+fn slice(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    if let Value::String(string) = &arguments[0] {
+        return slice_string(string, &arguments[1], &arguments[2]);
+    }
+    let list: Rc<RefCell<Vec<Value>>> = expect_list(&arguments[0])?;
+    let elements = list.borrow();
+    let (start, end): (usize, usize) = bounds(&arguments[1], &arguments[2], elements.len())?;
+
+    return Ok(Value::new_list(elements[start..end].to_vec()));
+}
+
+fn slice_string(string: &str, start: &Value, end: &Value) -> Result<Value, RuntimeError> {
+    let (start, end): (usize, usize) = bounds(start, end, string.chars().count())?;
+    return Ok(Value::String(
+        string.chars().skip(start).take(end - start).collect(),
+    ));
+}
+
+// Positions of the bounds of a slice of `len` elements, end never before start.
+fn bounds(start: &Value, end: &Value, len: usize) -> Result<(usize, usize), RuntimeError> {
+    let clamp = |bound: &Value| -> Result<usize, RuntimeError> {
+        let bound: i64 = match bound {
+            Value::Integer(bound) => *bound,
+            _ => {
+                return Err(RuntimeError::Native(String::from(
+                    "Slice bounds must be integers.",
+                )))
+            }
+        };
+        let len: i64 = len as i64;
+        let bound: i64 = if bound < 0 { bound + len } else { bound };
+        return Ok(bound.clamp(0, len) as usize);
+    };
+    let start: usize = clamp(start)?;
+    return Ok((start, clamp(end)?.max(start)));
+}
+
+// keys(map) -> list of the keys of map, in insertion order.
+fn keys(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let map: Rc<RefCell<Map>> = expect_map(&arguments[0])?;
+    let keys: Vec<Value> = map
+        .borrow()
+        .get_entries()
+        .iter()
+        .map(|(key, _)| key.to_value())
+        .collect();
+    return Ok(Value::new_list(keys));
+}
+
+// values(map) -> list of the values of map, in insertion order.
+fn values(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let map: Rc<RefCell<Map>> = expect_map(&arguments[0])?;
+    let values: Vec<Value> = map
+        .borrow()
+        .get_entries()
+        .iter()
+        .map(|(_, value)| value.clone())
+        .collect();
+    return Ok(Value::new_list(values));
+}
+
+// has(map, key) -> whether key is in map.
+fn has(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let map: Rc<RefCell<Map>> = expect_map(&arguments[0])?;
+    let key: Key = expect_key(&arguments[1])?;
+    return Ok(Value::Boolean(map.borrow().contains(&key)));
+}
+
+fn expect_map(value: &Value) -> Result<Rc<RefCell<Map>>, RuntimeError> {
+    return match value {
+        Value::Map(map) => Ok(map.clone()),
+        _ => Err(RuntimeError::Native(format!(
+            "Expect a map but got {}.",
+            value.describe()
+        ))),
+    };
+}
+
+fn expect_key(value: &Value) -> Result<Key, RuntimeError> {
+    return Key::from_value(value).map_err(RuntimeError::Native);
+}
+
+// string.upper() -> string in upper case.
+fn upper(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    return Ok(Value::String(expect_string(&arguments[0])?.to_uppercase()));
+}
+
+// string.lower() -> string in lower case.
+fn lower(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    return Ok(Value::String(expect_string(&arguments[0])?.to_lowercase()));
+}
+
+// string.trim() -> string without whitespace at either end.
+fn trim(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    return Ok(Value::String(String::from(
+        expect_string(&arguments[0])?.trim(),
+    )));
+}
+
+// string.split(separator) -> list of the parts of string between separators. An empty
+// separator splits it into characters.
+fn split(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let string: &str = expect_string(&arguments[0])?;
+    let separator: &str = expect_string(&arguments[1])?;
+
+    let parts: Vec<Value> = match separator.is_empty() {
+        true => string
+            .chars()
+            .map(|char| Value::String(char.to_string()))
+            .collect(),
+        false => string
+            .split(separator)
+            .map(|part| Value::String(String::from(part)))
+            .collect(),
+    };
+    return Ok(Value::new_list(parts));
+}
+
+// separator.join(list) -> the elements of list as print shows them, separated by separator.
+fn join(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let separator: &str = expect_string(&arguments[0])?;
+    let list: Rc<RefCell<Vec<Value>>> = expect_list(&arguments[1])?;
+
+    let parts: Vec<String> = list.borrow().iter().map(Value::to_string).collect();
+    return Ok(Value::String(parts.join(separator)));
+}
+
+// string.find(part) -> index of the first character of part in string, -1 if it is not there.
+fn find(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let string: &str = expect_string(&arguments[0])?;
+    let part: &str = expect_string(&arguments[1])?;
+
+    let index: i64 = match string.find(part) {
+        Some(byte) => string[..byte].chars().count() as i64,
+        None => -1,
+    };
+    return Ok(Value::Integer(index));
+}
+
+// string.replace(from, to) -> string with every occurrence of from replaced by to.
+fn replace(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let string: &str = expect_string(&arguments[0])?;
+    let from: &str = expect_string(&arguments[1])?;
+    let to: &str = expect_string(&arguments[2])?;
+
+    // An empty `from` matches between every two characters, as it does for str::replace.
+    let matches: usize = string.matches(from).count();
+    if (string.len() - matches * from.len())
+        .checked_add(matches.saturating_mul(to.len()))
+        .is_none_or(|length| length > MAX_STRING_LENGTH)
+    {
+        return fail(String::from("Replaced string is too long."));
+    }
+    return Ok(Value::String(string.replace(from, to)));
+}
+
+// string.starts_with(prefix) -> whether string starts with prefix.
+fn starts_with(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let string: &str = expect_string(&arguments[0])?;
+    let prefix: &str = expect_string(&arguments[1])?;
+    return Ok(Value::Boolean(string.starts_with(prefix)));
+}
+
+// string.ends_with(suffix) -> whether string ends with suffix.
+fn ends_with(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let string: &str = expect_string(&arguments[0])?;
+    let suffix: &str = expect_string(&arguments[1])?;
+    return Ok(Value::Boolean(string.ends_with(suffix)));
+}
+
+// string.repeat(count) -> string repeated count times.
+fn repeat(_: &Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let string: &str = expect_string(&arguments[0])?;
+    let count: usize = match &arguments[1] {
+        Value::Integer(count) if *count >= 0 => *count as usize,
+        _ => return fail(String::from("Repeat count must be a non-negative integer.")),
+    };
+    if string
+        .len()
+        .checked_mul(count)
+        .is_none_or(|length| length > MAX_STRING_LENGTH)
+    {
+        return fail(String::from("Repeated string is too long."));
+    }
+    return Ok(Value::String(string.repeat(count)));
+}
+
+fn expect_string(value: &Value) -> Result<&str, RuntimeError> {
+    return match value {
+        Value::String(string) => Ok(string),
+        _ => Err(RuntimeError::Native(format!(
+            "Expect a string but got {}.",
+            value.describe()
+        ))),
+    };
+}
+
+fn expect_list(value: &Value) -> Result<Rc<RefCell<Vec<Value>>>, RuntimeError> {
+    return match value {
+        Value::List(list) => Ok(list.clone()),
+        _ => Err(RuntimeError::Native(format!(
+            "Expect a list but got {}.",
+            value.describe()
+        ))),
+    };
+}
+
+// Position of an index argument in a list of `len` elements.
+fn expect_position(index: &Value, len: usize) -> Result<usize, RuntimeError> {
+    let index: i64 = match index {
+        Value::Integer(index) => *index,
+        _ => {
+            return Err(RuntimeError::Native(String::from(
+                "List index must be an integer.",
+            )))
+        }
+    };
+    return match interpreter::position(index, len) {
+        Some(position) => Ok(position),
+        None => Err(RuntimeError::Native(format!(
+            "Index {} is out of bounds for a list of length {}.",
+            index, len
+        ))),
+    };
+}
+
+fn fail(message: String) -> Result<Value, RuntimeError> {
+    return Err(RuntimeError::Native(message));
+}
@@ -2,4 +2,4 @@ pub mod scanner;
 pub mod token;
 
 pub use scanner::Scanner;
-pub use token::{Token, TokenType};
+pub use token::{Comment, Token, TokenType, Trivia, TriviaKind};
@@ -1,14 +1,30 @@
 use std::collections::HashMap;
 
-use super::token::{Token, TokenType};
-use crate::utils;
+use unicode_xid::UnicodeXID;
+
+use super::token::{Comment, Token, TokenType, Trivia, TriviaKind};
+use crate::utils::{self, SyntaxError};
 
 pub struct Scanner<'a> {
     source: &'a str,
     tokens: Vec<Token>,
+    comments: Vec<Comment>,
+    errors: Vec<SyntaxError>,
+    // byte offsets into source, always at character boundaries.
     start: usize,
     current: usize,
     line: u32,
+    // 1-based column of current, in characters.
+    column: u32,
+    // position of the token being scanned.
+    start_line: u32,
+    start_column: u32,
+    // When set, whitespace, comments and bad input are kept on the tokens as trivia.
+    lossless: bool,
+    // Trivia waiting for the next token, i.e. everything after the last newline.
+    pending_trivia: Vec<Trivia>,
+    // One entry per "${" not closed yet, counting the "{" opened inside it since.
+    interpolations: Vec<usize>,
 }
 
 impl<'a> Scanner<'a> {
@@ -16,40 +32,78 @@ impl<'a> Scanner<'a> {
         return Self {
             source,
             tokens: Vec::new(),
+            comments: Vec::new(),
+            errors: Vec::new(),
             start: 0,
             current: 0,
             line: 1,
+            column: 1,
+            start_line: 1,
+            start_column: 1,
+            lossless: false,
+            pending_trivia: Vec::new(),
+            interpolations: Vec::new(),
         };
     }
 
+    // A scanner whose tokens can be turned back into the source byte for byte
+    // (see Token::to_source and parser::cst).
+    pub fn new_lossless(source: &'a str) -> Scanner<'a> {
+        let mut scanner: Scanner = Self::new(source);
+        scanner.lossless = true;
+        return scanner;
+    }
+
     pub fn scan_tokens(&mut self, had_err: &mut bool) -> &Vec<Token> {
         while !self.is_at_end() {
             self.start = self.current;
+            self.start_line = self.line;
+            self.start_column = self.column();
             self.scan_token(had_err);
         }
 
-        self.tokens
-            .push(Token::new(TokenType::Eof, String::new(), self.line));
+        self.push_token(Token::new(
+            TokenType::Eof,
+            String::new(),
+            self.line,
+            self.column(),
+        ));
         &self.tokens
     }
 
+    pub fn get_comments(&self) -> &Vec<Comment> {
+        return &self.comments;
+    }
+
+    pub fn get_errors(&self) -> &Vec<SyntaxError> {
+        return &self.errors;
+    }
+
     fn scan_token(&mut self, had_err: &mut bool) {
         // Mapping literals to keywords?
         let keywords_map: HashMap<&str, TokenType> = HashMap::from([
             ("and", TokenType::And),
+            ("break", TokenType::Break),
+            ("catch", TokenType::Catch),
             ("class", TokenType::Class),
+            ("continue", TokenType::Continue),
             ("else", TokenType::Else),
             ("false", TokenType::False),
+            ("finally", TokenType::Finally),
             ("fun", TokenType::Fun),
             ("for", TokenType::For),
             ("if", TokenType::If),
+            ("import", TokenType::Import),
+            ("in", TokenType::In),
             ("nil", TokenType::Nil),
             ("or", TokenType::Or),
             ("print", TokenType::Print),
             ("return", TokenType::Return),
             ("super", TokenType::Super),
             ("this", TokenType::This),
+            ("throw", TokenType::Throw),
             ("true", TokenType::True),
+            ("try", TokenType::Try),
             ("var", TokenType::Var),
             ("while", TokenType::While),
         ]);
@@ -57,14 +111,41 @@ impl<'a> Scanner<'a> {
         match self.advance() {
             '(' => self.add_token(TokenType::LeftParen),
             ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
+            '{' => {
+                if let Some(braces) = self.interpolations.last_mut() {
+                    *braces += 1;
+                }
+                self.add_token(TokenType::LeftBrace);
+            }
+            // Closes an interpolated expression, the string goes on.
+            '}' if self.interpolations.last() == Some(&0) => {
+                self.interpolations.pop();
+                self.string_token(had_err);
+            }
+            '}' => {
+                if let Some(braces) = self.interpolations.last_mut() {
+                    *braces -= 1;
+                }
+                self.add_token(TokenType::RightBrace);
+            }
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
-            '-' => self.add_token(TokenType::Minus),
-            '+' => self.add_token(TokenType::Plus),
+            '.' => match (self.peek(), self.peek_next()) {
+                ('.', '=') => {
+                    self.advance();
+                    self.add_token_of_two(TokenType::DotDotEqual);
+                }
+                ('.', _) => self.add_token_of_two(TokenType::DotDot),
+                _ => self.add_token(TokenType::Dot),
+            },
             ';' => self.add_token(TokenType::Semicolon),
-            '*' => self.add_token(TokenType::Star),
+            '&' => self.add_token(TokenType::Ampersand),
+            '|' => self.add_token(TokenType::Pipe),
+            '^' => self.add_token(TokenType::Caret),
+            '~' => self.add_token(TokenType::Tilde),
+            '?' => self.add_token(TokenType::Question),
+            ':' => self.add_token(TokenType::Colon),
 
             '!' => match self.expected_match('=') {
                 true => self.add_token(TokenType::BangEqual),
@@ -74,36 +155,69 @@ impl<'a> Scanner<'a> {
                 true => self.add_token(TokenType::EqualEqual),
                 false => self.add_token(TokenType::Equal),
             },
-            '<' => match self.expected_match('=') {
-                true => self.add_token(TokenType::LessEqual),
-                false => self.add_token(TokenType::Less),
+            '<' => match self.peek() {
+                '=' => self.add_token_of_two(TokenType::LessEqual),
+                '<' => self.add_token_of_two(TokenType::LessLess),
+                _ => self.add_token(TokenType::Less),
             },
-            '>' => match self.expected_match('=') {
-                true => self.add_token(TokenType::GreaterEqual),
-                false => self.add_token(TokenType::Greater),
+            '>' => match self.peek() {
+                '=' => self.add_token_of_two(TokenType::GreaterEqual),
+                '>' => self.add_token_of_two(TokenType::GreaterGreater),
+                _ => self.add_token(TokenType::Greater),
             },
-            '/' => match self.expected_match('/') {
-                true => {
-                    while self.peek() != '\n' && !self.is_at_end() {
-                        self.advance();
-                    }
-                }
-                false => self.add_token(TokenType::Slash),
+            '-' => match self.expected_match('=') {
+                true => self.add_token(TokenType::MinusEqual),
+                false => self.add_token(TokenType::Minus),
             },
-
-            ' ' | '\r' | '\t' => (),
-            '\n' => self.line += 1,
-
-            '"' => match self.string() {
-                Ok(string_literal) => self.add_token(TokenType::String(string_literal)),
-                Err(err_msg) => utils::lex_error(self.line, err_msg, had_err),
+            '+' => match self.expected_match('=') {
+                true => self.add_token(TokenType::PlusEqual),
+                false => self.add_token(TokenType::Plus),
+            },
+            '%' => match self.expected_match('=') {
+                true => self.add_token(TokenType::PercentEqual),
+                false => self.add_token(TokenType::Percent),
+            },
+            '*' => match self.peek() {
+                '*' => self.add_token_of_two(TokenType::StarStar),
+                '=' => self.add_token_of_two(TokenType::StarEqual),
+                _ => self.add_token(TokenType::Star),
+            },
+            '/' => match self.peek() {
+                '/' => self.line_comment(),
+                '*' => self.block_comment(had_err),
+                '=' => self.add_token_of_two(TokenType::SlashEqual),
+                _ => self.add_token(TokenType::Slash),
             },
 
-            '0'..='9' => {
-                let number: f32 = self.number();
-                self.add_token(TokenType::Number(number));
+            ' ' | '\r' | '\t' => self.add_trivia(TriviaKind::Whitespace),
+            '\n' => {
+                self.line += 1;
+                self.add_trivia(TriviaKind::Newline);
             }
-            'a'..='z' | 'A'..='Z' | '_' => {
+
+            '"' => self.string_token(had_err),
+            'r' if self.source[self.current..]
+                .trim_start_matches('#')
+                .starts_with('"') =>
+            {
+                match self.raw_string() {
+                    Ok(string_literal) => self.add_token(TokenType::String(string_literal)),
+                    Err(err_msg) => {
+                        self.error(err_msg, had_err);
+                        self.add_trivia(TriviaKind::Skipped);
+                    }
+                }
+            }
+
+            c @ '0'..='9' => match self.number(c) {
+                Ok(token_type) => self.add_token(token_type),
+                // Still a number, so the parser does not report it again.
+                Err(err_msg) => {
+                    self.error(&err_msg, had_err);
+                    self.add_token(TokenType::Number(0.0));
+                }
+            },
+            c if Self::is_alpha(c) => {
                 let identifier: String = String::from(self.identifier());
 
                 match keywords_map.get(&identifier[..]) {
@@ -112,8 +226,60 @@ impl<'a> Scanner<'a> {
                 }
             }
 
-            _ => utils::lex_error(self.line, "unexpected character.", had_err),
+            _ => {
+                self.error("unexpected character.", had_err);
+                self.add_trivia(TriviaKind::Skipped);
+            }
+        }
+    }
+
+    // "///" starts a doc comment, "////" and more a plain one again.
+    fn line_comment(&mut self) {
+        while self.peek() != '\n' && !self.is_at_end() {
+            self.advance();
+        }
+        let text: &str = &self.source[self.start..self.current];
+        let kind: TriviaKind = match text.starts_with("///") && !text.starts_with("////") {
+            true => TriviaKind::DocComment,
+            false => TriviaKind::Comment,
+        };
+
+        self.comments
+            .push(Comment::new(String::from(text), self.start_line));
+        self.add_trivia(kind);
+    }
+
+    // Block comments nest, "/* a /* b */ c */" is one comment.
+    fn block_comment(&mut self, had_err: &mut bool) {
+        // Consuming the "*".
+        self.advance();
+        let mut depth: usize = 1;
+
+        while depth > 0 {
+            if self.is_at_end() {
+                self.error("Unterminated block comment.", had_err);
+                self.add_trivia(TriviaKind::Skipped);
+                return;
+            }
+            match self.advance() {
+                '/' if self.peek() == '*' => {
+                    self.advance();
+                    depth += 1;
+                }
+                '*' if self.peek() == '/' => {
+                    self.advance();
+                    depth -= 1;
+                }
+                '\n' => self.line += 1,
+                _ => (),
+            }
         }
+
+        self.comments.push(Comment::new(
+            String::from(&self.source[self.start..self.current]),
+            self.start_line,
+        ));
+        self.add_trivia(TriviaKind::Comment);
     }
 
     fn identifier(&mut self) -> &str {
@@ -124,49 +290,141 @@ impl<'a> Scanner<'a> {
         return &self.source[self.start..self.current];
     }
 
+    // Identifiers follow Unicode's XID_Start XID_Continue*, with "_" allowed to start them too.
     fn is_alpha(c: char) -> bool {
-        return match c {
-            'a'..='z' | 'A'..='Z' | '_' => true,
-            _ => false,
-        };
+        return c == '_' || c.is_xid_start();
     }
 
     fn is_alnum(c: char) -> bool {
-        return Self::is_alpha(c) || Self::is_digit(c);
+        return c.is_xid_continue();
     }
 
-    fn number(&mut self) -> f32 {
-        while Self::is_digit(self.peek()) {
-            self.advance();
+    // number -> DIGITS ( "." DIGITS )? ( ( "e" | "E" ) ( "+" | "-" )? DIGITS )?
+    //         | "0" ( "x" | "b" | "o" ) <digits of that base> ;
+    // "_" may separate digits. Without a fraction or exponent it is an integer.
+    fn number(&mut self, first: char) -> Result<TokenType, String> {
+        if first == '0' {
+            let base: Option<(u32, &str)> = match self.peek() {
+                'x' | 'X' => Some((16, "hexadecimal")),
+                'b' | 'B' => Some((2, "binary")),
+                'o' | 'O' => Some((8, "octal")),
+                _ => None,
+            };
+            if let Some((radix, name)) = base {
+                self.advance();
+                return self.radix_number(radix, name);
+            }
         }
+
+        self.digits();
         // Look for decimal part.
         if self.peek() == '.' && Self::is_digit(self.peek_next()) {
             // Consume the ".".
             self.advance();
-
-            while Self::is_digit(self.peek()) {
+            self.digits();
+        }
+        if self.peek() == 'e' || self.peek() == 'E' {
+            self.advance();
+            if self.peek() == '+' || self.peek() == '-' {
                 self.advance();
             }
+            let exponent_start: usize = self.current;
+            self.digits();
+            if self.current == exponent_start {
+                return Err(String::from("Expect digits after the exponent."));
+            }
         }
 
-        return (&self.source[self.start..self.current])
+        let literal: &str = &self.source[self.start..self.current];
+        for digits in literal.split(|c: char| !Self::is_digit(c) && c != '_') {
+            Self::check_separators(digits)?;
+        }
+        let literal: String = literal.replace('_', "");
+        if literal.chars().all(Self::is_digit) {
+            return Self::integer(&literal, 10);
+        }
+        // Checked above, so this only fails on input that is not a number at all.
+        return literal
             .parse::<f32>()
-            .unwrap();
+            .map(TokenType::Number)
+            .map_err(|_| String::from("Invalid number."));
+    }
+
+    fn integer(digits: &str, radix: u32) -> Result<TokenType, String> {
+        return i64::from_str_radix(digits, radix)
+            .map(TokenType::Integer)
+            .map_err(|_| String::from("Integer literal is too large."));
+    }
+
+    fn digits(&mut self) {
+        while Self::is_digit(self.peek()) || self.peek() == '_' {
+            self.advance();
+        }
+    }
+
+    // The digits after "0x", "0b" or "0o". Letters and digits right after the prefix are all
+    // taken as digits, so "0b102" is one bad literal rather than a number and then another.
+    fn radix_number(&mut self, radix: u32, name: &str) -> Result<TokenType, String> {
+        let prefix: &str = &self.source[self.start..self.current];
+        let digits_start: usize = self.current;
+        while Self::is_alnum(self.peek()) {
+            self.advance();
+        }
+        let digits: &str = &self.source[digits_start..self.current];
+
+        if digits.is_empty() {
+            return Err(format!("Expect {} digits after '{}'.", name, prefix));
+        }
+        if let Some(digit) = digits.chars().find(|c| *c != '_' && !c.is_digit(radix)) {
+            return Err(format!("Invalid digit '{}' in {} number.", digit, name));
+        }
+        Self::check_separators(digits)?;
+
+        return Self::integer(&digits.replace('_', ""), radix);
+    }
+
+    fn check_separators(digits: &str) -> Result<(), String> {
+        if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+            return Err(String::from("Digit separators must be between digits."));
+        }
+        return Ok(());
     }
 
     fn is_digit(c: char) -> bool {
-        return match c {
-            '0'..='9' => true,
-            _ => false,
-        };
+        return c.is_ascii_digit();
+    }
+
+    fn string_token(&mut self, had_err: &mut bool) {
+        match self.string(had_err) {
+            Ok(token_type) => self.add_token(token_type),
+            Err(err_msg) => {
+                self.error(err_msg, had_err);
+                self.add_trivia(TriviaKind::Skipped);
+            }
+        }
     }
 
-    fn string(&mut self) -> Result<String, &'static str> {
+    // The string up to its closing '"', or up to a "${" which makes it an Interpolation.
+    // Invalid escapes are reported where they are, the string still makes a token.
+    fn string(&mut self, had_err: &mut bool) -> Result<TokenType, &'static str> {
+        let mut string: String = String::new();
+
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+            match self.peek() {
+                '\\' => self.escape(&mut string, had_err),
+                '$' if self.peek_next() == '{' => {
+                    self.advance();
+                    self.advance();
+                    self.interpolations.push(0);
+                    return Ok(TokenType::Interpolation(string));
+                }
+                c => {
+                    if c == '\n' {
+                        self.line += 1;
+                    }
+                    string.push(self.advance());
+                }
             }
-            self.advance();
         }
 
         if self.is_at_end() {
@@ -176,47 +434,201 @@ impl<'a> Scanner<'a> {
         // Consuming the closing ".
         self.advance();
 
-        return Ok(String::from(&self.source[self.start + 1..self.current - 1]));
+        return Ok(TokenType::String(string));
     }
 
-    fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
+    // escape -> "\\" ( "n" | "t" | "\\" | "\"" | "$" | "u{" HEX_DIGIT{1,6} "}" ) ;
+    fn escape(&mut self, string: &mut String, had_err: &mut bool) {
+        let start: usize = self.current;
+        let column: u32 = self.column();
+        self.advance();
+
+        let escaped: Option<char> = match self.peek() {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            '\\' => Some('\\'),
+            '"' => Some('"'),
+            '$' => Some('$'),
+            'u' => {
+                self.advance();
+                match self.unicode_escape() {
+                    Some(c) => string.push(c),
+                    None => self.error_at(
+                        column,
+                        start,
+                        &format!(
+                            "Invalid unicode escape '{}'.",
+                            &self.source[start..self.current]
+                        ),
+                        had_err,
+                    ),
+                }
+                return;
+            }
+            _ => None,
+        };
+
+        if !self.is_at_end() && self.peek() != '\n' {
+            self.advance();
+        }
+        match escaped {
+            Some(c) => string.push(c),
+            None => self.error_at(
+                column,
+                start,
+                &format!(
+                    "Invalid escape sequence '{}'.",
+                    &self.source[start..self.current]
+                ),
+                had_err,
+            ),
         }
-        return self.source.as_bytes()[self.current + 1] as char;
     }
-    fn peek(&self) -> char {
-        if self.is_at_end() {
-            return '\0';
+
+    // The character of a \u escape whose "u" has been consumed.
+    fn unicode_escape(&mut self) -> Option<char> {
+        if !self.expected_match('{') {
+            return None;
+        }
+        let digits_start: usize = self.current;
+        while self.peek().is_ascii_hexdigit() {
+            self.advance();
+        }
+        let digits: &str = &self.source[digits_start..self.current];
+        if !self.expected_match('}') || digits.is_empty() || digits.len() > 6 {
+            return None;
         }
-        return self.source.as_bytes()[self.current] as char;
+
+        return u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(char::from_u32);
     }
 
-    fn expected_match(&mut self, expected: char) -> bool {
-        if self.is_at_end() {
-            return false;
+    // raw_string -> "r" "#"* '"' ( any char )* '"' "#"* ; closed by as many "#" as opened it.
+    // Nothing is escaped, "#" allow '"' in the string.
+    fn raw_string(&mut self) -> Result<String, &'static str> {
+        let mut hashes: usize = 0;
+        while self.expected_match('#') {
+            hashes += 1;
         }
-        if self.source.as_bytes()[self.current] as char != expected {
+        // Consuming the opening ".
+        self.advance();
+        let content_start: usize = self.current;
+
+        loop {
+            if self.is_at_end() {
+                return Err("Unterminated string.");
+            }
+            match self.advance() {
+                '\n' => self.line += 1,
+                '"' if self.source[self.current..].starts_with(&"#".repeat(hashes)) => {
+                    let content_end: usize = self.current - 1;
+                    self.current += hashes;
+                    self.column += hashes as u32;
+                    return Ok(String::from(&self.source[content_start..content_end]));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn peek_next(&self) -> char {
+        return self.source[self.current..].chars().nth(1).unwrap_or('\0');
+    }
+    fn peek(&self) -> char {
+        return self.source[self.current..].chars().next().unwrap_or('\0');
+    }
+
+    fn expected_match(&mut self, expected: char) -> bool {
+        if self.is_at_end() || self.peek() != expected {
             return false;
         }
 
-        self.current += 1;
+        self.advance();
         return true;
     }
 
     fn advance(&mut self) -> char {
-        let c: char = self.source.as_bytes()[self.current] as char;
-        self.current += 1;
+        let c: char = self.peek();
+        self.current += c.len_utf8();
+        self.column = match c {
+            '\n' => 1,
+            _ => self.column + 1,
+        };
         return c;
     }
 
+    // 1-based column of the current position, in characters.
+    fn column(&self) -> u32 {
+        return self.column;
+    }
+
     fn add_token(&mut self, token_type: TokenType) {
         let new_token: Token = Token::new(
             token_type,
             String::from(&self.source[self.start..self.current]),
-            self.line,
+            self.start_line,
+            self.start_column,
         );
-        self.tokens.push(new_token);
+        self.push_token(new_token);
+    }
+
+    // A token whose second character has only been peeked at.
+    fn add_token_of_two(&mut self, token_type: TokenType) {
+        self.advance();
+        self.add_token(token_type);
+    }
+
+    // Error on the token being scanned.
+    fn error(&mut self, message: &str, had_err: &mut bool) {
+        utils::lex_error(self.start_line, message, had_err);
+        self.errors.push(SyntaxError::new(
+            self.start_line,
+            self.start_column,
+            self.current - self.start,
+            String::from(message),
+        ));
+    }
+
+    // Error on the part of the token from byte `start`, at `column` of the current line, up to
+    // the current position.
+    fn error_at(&mut self, column: u32, start: usize, message: &str, had_err: &mut bool) {
+        utils::lex_error(self.line, message, had_err);
+        self.errors.push(SyntaxError::new(
+            self.line,
+            column,
+            self.current - start,
+            String::from(message),
+        ));
+    }
+
+    fn push_token(&mut self, mut token: Token) {
+        if self.lossless {
+            token.set_leading_trivia(std::mem::take(&mut self.pending_trivia));
+        }
+        self.tokens.push(token);
+    }
+
+    fn add_trivia(&mut self, kind: TriviaKind) {
+        if !self.lossless {
+            return;
+        }
+
+        let text: &str = &self.source[self.start..self.current];
+        // Nothing pending means no newline since the last token, so this still belongs to its line.
+        let trivia: &mut Vec<Trivia> = match self.tokens.last_mut() {
+            Some(token) if kind != TriviaKind::Newline && self.pending_trivia.is_empty() => {
+                token.trailing_trivia_mut()
+            }
+            _ => &mut self.pending_trivia,
+        };
+
+        match trivia.last_mut() {
+            Some(last) if kind == TriviaKind::Whitespace && last.get_kind() == kind => {
+                last.push_str(text)
+            }
+            _ => trivia.push(Trivia::new(kind, String::from(text))),
+        }
     }
 
     fn is_at_end(&self) -> bool {
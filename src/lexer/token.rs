@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     // Single-character tokens.
@@ -5,6 +7,8 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
     Minus,
@@ -12,6 +16,13 @@ pub enum TokenType {
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    Question,
+    Colon,
 
     // one or two character tokens.
     Bang,
@@ -20,31 +31,54 @@ pub enum TokenType {
     EqualEqual,
     Greater,
     GreaterEqual,
+    GreaterGreater,
     Less,
     LessEqual,
+    LessLess,
+    StarStar,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PercentEqual,
+    DotDot,
+    DotDotEqual,
 
     // Keywords.
     And,
+    Break,
+    Catch,
     Class,
+    Continue,
     Else,
     False,
+    Finally,
     Fun,
     For,
     If,
+    Import,
+    In,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
 
     // Literals.
     Identifier(String),
     String(String),
+    // The part of an interpolated string up to a "${"; the expression inside follows, then the
+    // rest of the string as another Interpolation or a String.
+    Interpolation(String),
     Number(f32),
+    // A number without fraction or exponent.
+    Integer(i64),
 
     Eof,
 }
@@ -54,14 +88,22 @@ pub struct Token {
     token_type: TokenType,
     lexeme: String,
     line: u32,
+    // 1-based, counted in characters from the start of the line.
+    column: u32,
+    // Only filled in by a lossless scanner (see Scanner::new_lossless).
+    leading_trivia: Vec<Trivia>,
+    trailing_trivia: Vec<Trivia>,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, line: u32) -> Token {
+    pub fn new(token_type: TokenType, lexeme: String, line: u32, column: u32) -> Token {
         return Self {
             token_type,
             lexeme,
             line,
+            column,
+            leading_trivia: Vec::new(),
+            trailing_trivia: Vec::new(),
         };
     }
     pub fn get_token_type(&self) -> &TokenType {
@@ -73,7 +115,95 @@ impl Token {
     pub fn get_line(&self) -> u32 {
         return self.line;
     }
-    pub fn to_string(&self) -> String {
-        return format!("{:?} {}", self.token_type, self.lexeme);
+    pub fn get_column(&self) -> u32 {
+        return self.column;
+    }
+    pub fn get_leading_trivia(&self) -> &Vec<Trivia> {
+        return &self.leading_trivia;
+    }
+    pub fn get_trailing_trivia(&self) -> &Vec<Trivia> {
+        return &self.trailing_trivia;
+    }
+    // The exact source text this token was scanned from, trivia included.
+    pub fn to_source(&self) -> String {
+        let mut source: String = String::new();
+
+        for trivia in &self.leading_trivia {
+            source.push_str(trivia.get_text());
+        }
+        source.push_str(&self.lexeme);
+        for trivia in &self.trailing_trivia {
+            source.push_str(trivia.get_text());
+        }
+
+        return source;
+    }
+    pub(crate) fn set_leading_trivia(&mut self, trivia: Vec<Trivia>) {
+        self.leading_trivia = trivia;
+    }
+    pub(crate) fn trailing_trivia_mut(&mut self) -> &mut Vec<Trivia> {
+        return &mut self.trailing_trivia;
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{:?} {}", self.token_type, self.lexeme);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Comment {
+    text: String,
+    line: u32,
+}
+
+impl Comment {
+    pub fn new(text: String, line: u32) -> Comment {
+        return Self { text, line };
+    }
+    pub fn get_text(&self) -> &str {
+        return &self.text;
+    }
+    pub fn get_line(&self) -> u32 {
+        return self.line;
+    }
+    // Last line of the comment, later than get_line for block comments spanning lines.
+    pub fn get_end_line(&self) -> u32 {
+        return self.line + self.text.matches('\n').count() as u32;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriviaKind {
+    Whitespace,
+    Newline,
+    Comment,
+    // A "///" comment, documenting what follows it.
+    DocComment,
+    // Source the scanner reported an error for, e.g. an unexpected character.
+    Skipped,
+}
+
+// Source text between tokens. A token owns the trivia on its own line after it (trailing) and
+// everything from the preceding newline up to it (leading).
+#[derive(Debug, Clone)]
+pub struct Trivia {
+    kind: TriviaKind,
+    text: String,
+}
+
+impl Trivia {
+    pub fn new(kind: TriviaKind, text: String) -> Trivia {
+        return Self { kind, text };
+    }
+    pub fn get_kind(&self) -> TriviaKind {
+        return self.kind;
+    }
+    pub fn get_text(&self) -> &str {
+        return &self.text;
+    }
+    pub(crate) fn push_str(&mut self, text: &str) {
+        self.text.push_str(text);
     }
 }
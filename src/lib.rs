@@ -0,0 +1,17 @@
+// The codebase spells out `return`s and field names on purpose.
+#![allow(
+    clippy::needless_return,
+    clippy::redundant_field_names,
+    clippy::module_inception,
+    clippy::ptr_arg,
+    clippy::result_large_err
+)]
+
+pub mod dap;
+pub mod debugger;
+pub mod interpreter;
+pub mod lexer;
+pub mod lsp;
+pub mod parser;
+pub mod testing;
+pub mod utils;
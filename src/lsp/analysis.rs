@@ -0,0 +1,794 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::interpreter::{Interpreter, Value};
+use crate::lexer::{Scanner, Token, TokenType, TriviaKind};
+use crate::parser::{Expr, Parser, Stmt, Visitor};
+use crate::utils::SyntaxError;
+
+// Zero-based line and UTF-16 offset within the line, as LSP counts them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    Variable,
+    Function,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    // the whole declaration.
+    pub range: Range,
+    // just the declared name.
+    pub selection_range: Range,
+}
+
+// Order of the token types in the semantic tokens legend, see `SemanticTokenType::index`.
+pub const SEMANTIC_TOKEN_TYPES: [&str; 6] = [
+    "keyword", "string", "number", "variable", "operator", "comment",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SemanticTokenType {
+    Keyword,
+    String,
+    Number,
+    Variable,
+    Operator,
+    Comment,
+}
+
+impl SemanticTokenType {
+    fn index(&self) -> u32 {
+        return *self as u32;
+    }
+
+    fn of(token_type: &TokenType) -> Option<SemanticTokenType> {
+        return match token_type {
+            TokenType::And
+            | TokenType::Break
+            | TokenType::Catch
+            | TokenType::Class
+            | TokenType::Continue
+            | TokenType::Else
+            | TokenType::False
+            | TokenType::Finally
+            | TokenType::Fun
+            | TokenType::For
+            | TokenType::If
+            | TokenType::Import
+            | TokenType::In
+            | TokenType::Nil
+            | TokenType::Or
+            | TokenType::Print
+            | TokenType::Return
+            | TokenType::Super
+            | TokenType::This
+            | TokenType::Throw
+            | TokenType::True
+            | TokenType::Try
+            | TokenType::Var
+            | TokenType::While => Some(Self::Keyword),
+            TokenType::String(_) | TokenType::Interpolation(_) => Some(Self::String),
+            TokenType::Number(_) | TokenType::Integer(_) => Some(Self::Number),
+            TokenType::Identifier(_) => Some(Self::Variable),
+            TokenType::Minus
+            | TokenType::Plus
+            | TokenType::Slash
+            | TokenType::Star
+            | TokenType::Bang
+            | TokenType::BangEqual
+            | TokenType::Equal
+            | TokenType::EqualEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::Less
+            | TokenType::LessEqual
+            | TokenType::Percent
+            | TokenType::StarStar
+            | TokenType::Ampersand
+            | TokenType::Pipe
+            | TokenType::Caret
+            | TokenType::Tilde
+            | TokenType::LessLess
+            | TokenType::GreaterGreater
+            | TokenType::PlusEqual
+            | TokenType::MinusEqual
+            | TokenType::StarEqual
+            | TokenType::SlashEqual
+            | TokenType::PercentEqual
+            | TokenType::DotDot
+            | TokenType::DotDotEqual
+            | TokenType::Question
+            | TokenType::Colon => Some(Self::Operator),
+            _ => None,
+        };
+    }
+}
+
+// An open text document together with what the scanner and parser made of it.
+pub struct Document {
+    text: String,
+    // byte offset of the start of every line.
+    line_starts: Vec<usize>,
+    tokens: Vec<Token>,
+    statements: Vec<Stmt>,
+    errors: Vec<SyntaxError>,
+    // every variable use paired with its declaration, declarations included.
+    references: Vec<(Token, Token)>,
+}
+
+impl Document {
+    pub fn new(text: String) -> Document {
+        let mut had_err: bool = false;
+
+        let mut scanner: Scanner = Scanner::new_lossless(&text);
+        let tokens: Vec<Token> = scanner.scan_tokens(&mut had_err).clone();
+        let mut errors: Vec<SyntaxError> = scanner.get_errors().clone();
+
+        let mut parser: Parser = Parser::new(tokens.clone(), &mut had_err);
+        let statements: Vec<Stmt> = parser.parse();
+        errors.extend(parser.get_errors().iter().cloned());
+
+        let mut line_starts: Vec<usize> = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(offset, _)| offset + 1));
+
+        let resolver: Resolver = Resolver::new();
+        resolver.statements(&statements);
+        let references: Vec<(Token, Token)> = resolver.references.into_inner();
+
+        return Self {
+            text,
+            line_starts,
+            tokens,
+            statements,
+            errors,
+            references,
+        };
+    }
+
+    pub fn get_errors(&self) -> &Vec<SyntaxError> {
+        return &self.errors;
+    }
+
+    pub fn error_range(&self, error: &SyntaxError) -> Range {
+        let start: usize = self.offset_of(error.get_line(), error.get_column());
+        return self.range(start, start + error.get_length());
+    }
+
+    // Type and value of the literal under the cursor, or of the constant expression whose
+    // operator is under it.
+    pub fn hover(&self, position: Position) -> Option<(Range, String)> {
+        let token: &Token = self.token_at(position)?;
+        let range: Range = self.token_range(token);
+
+        let value: Value = match token.get_token_type() {
+            TokenType::Number(number) => Value::Number(*number),
+            TokenType::Integer(integer) => Value::Integer(*integer),
+            // Only the end of an interpolated string starts with "}".
+            TokenType::String(string) if !token.get_lexeme().starts_with('}') => {
+                Value::String(string.clone())
+            }
+            TokenType::True => Value::Boolean(true),
+            TokenType::False => Value::Boolean(false),
+            TokenType::Nil => Value::Nil,
+            _ => {
+                let expr: &Expr = self.expressions().into_iter().find(|expr| {
+                    Self::operator(expr).is_some_and(|operator| Self::same(operator, token))
+                })?;
+                if !Self::is_constant(expr) {
+                    return None;
+                }
+
+                let mut had_runtime_err: bool = false;
+                let interpreter: Interpreter = Interpreter::new(&mut had_runtime_err);
+                interpreter.evaluate(expr.clone()).ok()?
+            }
+        };
+
+        let text: String = match &value {
+            Value::String(string) => format!("{}: \"{}\"", value.type_name(), string),
+            _ => format!("{}: {}", value.type_name(), value),
+        };
+        return Some((range, text));
+    }
+
+    // Declarations at the top level of the document.
+    pub fn symbols(&self) -> Vec<Symbol> {
+        let mut symbols: Vec<Symbol> = Vec::new();
+
+        for statement in &self.statements {
+            let (name, kind): (&Token, SymbolKind) = match statement {
+                Stmt::Var(var) => (&var.name, SymbolKind::Variable),
+                Stmt::Function(function) => (&function.name, SymbolKind::Function),
+                _ => continue,
+            };
+            let lines = statement.get_lines();
+
+            symbols.push(Symbol {
+                name: name.get_lexeme(),
+                kind,
+                range: self.lines_range(lines.start, lines.end),
+                selection_range: self.token_range(name),
+            });
+        }
+
+        return symbols;
+    }
+
+    // Where the variable under the cursor was declared.
+    pub fn definition(&self, position: Position) -> Option<Range> {
+        let token: &Token = self.token_at(position)?;
+
+        return self
+            .references
+            .iter()
+            .find(|(reference, _)| Self::same(reference, token))
+            .map(|(_, declaration)| self.token_range(declaration));
+    }
+
+    // Semantic tokens in the relative encoding of the LSP spec: five numbers per token.
+    pub fn semantic_tokens(&self) -> Vec<u32> {
+        let mut spans: Vec<(usize, usize, SemanticTokenType)> = Vec::new();
+        let mut offset: usize = 0;
+
+        for token in &self.tokens {
+            for trivia in token.get_leading_trivia() {
+                if matches!(
+                    trivia.get_kind(),
+                    TriviaKind::Comment | TriviaKind::DocComment
+                ) {
+                    spans.push((offset, trivia.get_text().len(), SemanticTokenType::Comment));
+                }
+                offset += trivia.get_text().len();
+            }
+
+            let length: usize = token.get_lexeme().len();
+            if let Some(token_type) = SemanticTokenType::of(token.get_token_type()) {
+                spans.push((offset, length, token_type));
+            }
+            offset += length;
+
+            for trivia in token.get_trailing_trivia() {
+                if matches!(
+                    trivia.get_kind(),
+                    TriviaKind::Comment | TriviaKind::DocComment
+                ) {
+                    spans.push((offset, trivia.get_text().len(), SemanticTokenType::Comment));
+                }
+                offset += trivia.get_text().len();
+            }
+        }
+
+        let mut data: Vec<u32> = Vec::new();
+        let mut previous: Position = Position {
+            line: 0,
+            character: 0,
+        };
+        for (start, length, token_type) in spans {
+            // A token may not span lines, so multi-line strings are sent one line at a time.
+            for (line_start, line_end) in self.split_lines(start, start + length) {
+                let range: Range = self.range(line_start, line_end);
+                let delta_start: u32 = match range.start.line == previous.line {
+                    true => range.start.character - previous.character,
+                    false => range.start.character,
+                };
+
+                data.extend([
+                    range.start.line - previous.line,
+                    delta_start,
+                    range.end.character - range.start.character,
+                    token_type.index(),
+                    0,
+                ]);
+                previous = range.start;
+            }
+        }
+
+        return data;
+    }
+
+    // The token under the cursor, or the one just before it when the cursor is at its end.
+    fn token_at(&self, position: Position) -> Option<&Token> {
+        let offset: usize = self.offset_at(position);
+        let span = |token: &Token| -> (usize, usize) {
+            let start: usize = self.offset_of(token.get_line(), token.get_column());
+            return (start, start + token.get_lexeme().len());
+        };
+
+        return self
+            .tokens
+            .iter()
+            .find(|token| {
+                let (start, end) = span(token);
+                start <= offset && offset < end
+            })
+            .or_else(|| {
+                self.tokens.iter().find(|token| {
+                    let (start, end) = span(token);
+                    start < end && end == offset
+                })
+            });
+    }
+
+    fn token_range(&self, token: &Token) -> Range {
+        let start: usize = self.offset_of(token.get_line(), token.get_column());
+        return self.range(start, start + token.get_lexeme().len());
+    }
+
+    fn lines_range(&self, start_line: u32, end_line: u32) -> Range {
+        let start: usize = self.offset_of(start_line, 1);
+        let end: usize = match self.line_starts.get(end_line as usize) {
+            Some(next_line) => next_line - 1,
+            None => self.text.len(),
+        };
+        return self.range(start, end);
+    }
+
+    // Byte offset of a 1-based line and column (in characters) as the scanner reports them.
+    fn offset_of(&self, line: u32, column: u32) -> usize {
+        let line_start: usize = match self.line_starts.get(line as usize - 1) {
+            Some(line_start) => *line_start,
+            None => self.text.len(),
+        };
+        return match self.text[line_start..]
+            .char_indices()
+            .nth(column as usize - 1)
+        {
+            Some((offset, _)) => line_start + offset,
+            None => self.text.len(),
+        };
+    }
+
+    fn offset_at(&self, position: Position) -> usize {
+        let line_start: usize = match self.line_starts.get(position.line as usize) {
+            Some(line_start) => *line_start,
+            None => return self.text.len(),
+        };
+
+        let mut character: u32 = 0;
+        for (offset, c) in self.text[line_start..].char_indices() {
+            if character >= position.character || c == '\n' {
+                return line_start + offset;
+            }
+            character += c.len_utf16() as u32;
+        }
+        return self.text.len();
+    }
+
+    fn position_at(&self, offset: usize) -> Position {
+        let line: usize = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let line_start: usize = self.line_starts[line];
+
+        return Position {
+            line: line as u32,
+            character: self.text[line_start..offset].encode_utf16().count() as u32,
+        };
+    }
+
+    fn range(&self, start: usize, end: usize) -> Range {
+        return Range {
+            start: self.position_at(start),
+            end: self.position_at(end.min(self.text.len())),
+        };
+    }
+
+    fn split_lines(&self, start: usize, end: usize) -> Vec<(usize, usize)> {
+        let mut parts: Vec<(usize, usize)> = Vec::new();
+        let mut part_start: usize = start;
+
+        for (offset, _) in self.text[start..end].match_indices('\n') {
+            parts.push((part_start, start + offset));
+            part_start = start + offset + 1;
+        }
+        if part_start < end {
+            parts.push((part_start, end));
+        }
+
+        return parts;
+    }
+
+    // Every expression in the document, outermost first.
+    fn expressions(&self) -> Vec<&Expr> {
+        let mut expressions: Vec<&Expr> = Vec::new();
+        let mut pending: Vec<&Stmt> = self.statements.iter().rev().collect();
+
+        while let Some(statement) = pending.pop() {
+            match statement {
+                Stmt::Expression(expression) => {
+                    Self::collect(&expression.expression, &mut expressions)
+                }
+                Stmt::Print(print) => Self::collect(&print.expression, &mut expressions),
+                Stmt::Var(var) => {
+                    if let Some(initializer) = &var.initializer {
+                        Self::collect(initializer, &mut expressions);
+                    }
+                }
+                Stmt::Block(block) => pending.extend(block.statements.iter().rev()),
+                Stmt::If(if_stmt) => {
+                    Self::collect(&if_stmt.condition, &mut expressions);
+                    if let Some(else_branch) = &if_stmt.else_branch {
+                        pending.push(else_branch);
+                    }
+                    pending.push(&if_stmt.then_branch);
+                }
+                Stmt::While(while_stmt) => {
+                    Self::collect(&while_stmt.condition, &mut expressions);
+                    pending.push(&while_stmt.body);
+                }
+                Stmt::ForIn(for_in) => {
+                    Self::collect(&for_in.iterable, &mut expressions);
+                    pending.push(&for_in.body);
+                }
+                Stmt::For(for_stmt) => {
+                    if let Some(condition) = &for_stmt.condition {
+                        Self::collect(condition, &mut expressions);
+                    }
+                    if let Some(increment) = &for_stmt.increment {
+                        Self::collect(increment, &mut expressions);
+                    }
+                    pending.push(&for_stmt.body);
+                    if let Some(initializer) = &for_stmt.initializer {
+                        pending.push(initializer);
+                    }
+                }
+                Stmt::Function(function) => pending.extend(function.body.iter().rev()),
+                Stmt::Test(test) => pending.extend(test.body.iter().rev()),
+                Stmt::Return(return_stmt) => {
+                    if let Some(value) = &return_stmt.value {
+                        Self::collect(value, &mut expressions);
+                    }
+                }
+                Stmt::Throw(throw) => Self::collect(&throw.value, &mut expressions),
+                Stmt::Try(try_stmt) => {
+                    if let Some(finally) = &try_stmt.finally {
+                        pending.extend(finally.statements.iter().rev());
+                    }
+                    if let Some((_, catch)) = &try_stmt.catch {
+                        pending.extend(catch.statements.iter().rev());
+                    }
+                    pending.extend(try_stmt.body.statements.iter().rev());
+                }
+                Stmt::Break(_) | Stmt::Continue(_) | Stmt::Import(_) => (),
+            }
+        }
+
+        return expressions;
+    }
+
+    fn collect<'e>(expr: &'e Expr, expressions: &mut Vec<&'e Expr>) {
+        expressions.push(expr);
+
+        match expr {
+            Expr::Literal(_) | Expr::Variable(_) => (),
+            Expr::Grouping(grouping) => Self::collect(&grouping.expression, expressions),
+            Expr::Unary(unary) => Self::collect(&unary.right, expressions),
+            Expr::Binary(binary) => {
+                Self::collect(&binary.left, expressions);
+                Self::collect(&binary.right, expressions);
+            }
+            Expr::Assign(assign) => Self::collect(&assign.value, expressions),
+            Expr::Conditional(conditional) => {
+                Self::collect(&conditional.condition, expressions);
+                Self::collect(&conditional.then_branch, expressions);
+                Self::collect(&conditional.else_branch, expressions);
+            }
+            Expr::Comma(comma) => {
+                Self::collect(&comma.left, expressions);
+                Self::collect(&comma.right, expressions);
+            }
+            Expr::Logical(logical) => {
+                Self::collect(&logical.left, expressions);
+                Self::collect(&logical.right, expressions);
+            }
+            Expr::Call(call) => {
+                Self::collect(&call.callee, expressions);
+                for argument in &call.arguments {
+                    Self::collect(argument, expressions);
+                }
+            }
+            Expr::Interpolation(interpolation) => {
+                for part in &interpolation.parts {
+                    Self::collect(part, expressions);
+                }
+            }
+            Expr::List(list) => {
+                for element in &list.elements {
+                    Self::collect(element, expressions);
+                }
+            }
+            Expr::Map(map) => {
+                for (key, value) in &map.entries {
+                    Self::collect(key, expressions);
+                    Self::collect(value, expressions);
+                }
+            }
+            Expr::Range(range) => {
+                Self::collect(&range.start, expressions);
+                Self::collect(&range.end, expressions);
+            }
+            Expr::Get(get) => Self::collect(&get.object, expressions),
+            Expr::Index(index) => {
+                Self::collect(&index.object, expressions);
+                Self::collect(&index.index, expressions);
+            }
+            Expr::SetIndex(set_index) => {
+                Self::collect(&set_index.object, expressions);
+                Self::collect(&set_index.index, expressions);
+                Self::collect(&set_index.value, expressions);
+            }
+        }
+    }
+
+    fn operator(expr: &Expr) -> Option<&Token> {
+        return match expr {
+            Expr::Unary(unary) => Some(&unary.operator),
+            Expr::Binary(binary) => Some(&binary.operator),
+            Expr::Logical(logical) => Some(&logical.operator),
+            Expr::Range(range) => Some(&range.operator),
+            _ => None,
+        };
+    }
+
+    // Whether evaluating `expr` needs nothing but the expression itself.
+    fn is_constant(expr: &Expr) -> bool {
+        return match expr {
+            Expr::Literal(_) => true,
+            Expr::Grouping(grouping) => Self::is_constant(&grouping.expression),
+            Expr::Unary(unary) => Self::is_constant(&unary.right),
+            Expr::Binary(binary) => {
+                Self::is_constant(&binary.left) && Self::is_constant(&binary.right)
+            }
+            Expr::Logical(logical) => {
+                Self::is_constant(&logical.left) && Self::is_constant(&logical.right)
+            }
+            Expr::Conditional(conditional) => {
+                Self::is_constant(&conditional.condition)
+                    && Self::is_constant(&conditional.then_branch)
+                    && Self::is_constant(&conditional.else_branch)
+            }
+            Expr::Comma(comma) => Self::is_constant(&comma.left) && Self::is_constant(&comma.right),
+            Expr::Interpolation(interpolation) => interpolation.parts.iter().all(Self::is_constant),
+            Expr::List(list) => list.elements.iter().all(Self::is_constant),
+            Expr::Map(map) => map
+                .entries
+                .iter()
+                .all(|(key, value)| Self::is_constant(key) && Self::is_constant(value)),
+            Expr::Range(range) => Self::is_constant(&range.start) && Self::is_constant(&range.end),
+            Expr::Get(get) => Self::is_constant(&get.object),
+            Expr::Index(index) => {
+                Self::is_constant(&index.object) && Self::is_constant(&index.index)
+            }
+            Expr::Variable(_) | Expr::Assign(_) | Expr::SetIndex(_) | Expr::Call(_) => false,
+        };
+    }
+
+    fn same(a: &Token, b: &Token) -> bool {
+        return a.get_line() == b.get_line() && a.get_column() == b.get_column();
+    }
+}
+
+// Pairs variable uses with the declaration in scope, following the interpreter's scoping rules.
+struct Resolver {
+    scopes: RefCell<Vec<HashMap<String, Token>>>,
+    references: RefCell<Vec<(Token, Token)>>,
+}
+
+impl Resolver {
+    fn new() -> Resolver {
+        return Self {
+            scopes: RefCell::new(vec![HashMap::new()]),
+            references: RefCell::new(Vec::new()),
+        };
+    }
+
+    fn statements(&self, statements: &Vec<Stmt>) {
+        for statement in statements {
+            statement.accept::<()>(self);
+        }
+    }
+
+    fn declare(&self, name: &Token) {
+        if let Some(scope) = self.scopes.borrow_mut().last_mut() {
+            scope.insert(name.get_lexeme(), name.clone());
+        }
+    }
+
+    fn resolve(&self, name: &Token) {
+        let scopes = self.scopes.borrow();
+
+        for scope in scopes.iter().rev() {
+            if let Some(declaration) = scope.get(&name.get_lexeme()) {
+                self.references
+                    .borrow_mut()
+                    .push((name.clone(), declaration.clone()));
+                return;
+            }
+        }
+    }
+}
+
+impl Visitor<Stmt, ()> for Resolver {
+    fn visit(&self, statement: &Stmt) {
+        match statement {
+            Stmt::Expression(expression) => expression.expression.accept::<()>(self),
+            Stmt::Print(print) => print.expression.accept::<()>(self),
+            Stmt::Var(var) => {
+                if let Some(initializer) = &var.initializer {
+                    initializer.accept::<()>(self);
+                }
+                self.declare(&var.name);
+                self.resolve(&var.name);
+            }
+            Stmt::Block(block) => {
+                self.scopes.borrow_mut().push(HashMap::new());
+                self.statements(&block.statements);
+                self.scopes.borrow_mut().pop();
+            }
+            Stmt::If(if_stmt) => {
+                if_stmt.condition.accept::<()>(self);
+                if_stmt.then_branch.accept::<()>(self);
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    else_branch.accept::<()>(self);
+                }
+            }
+            Stmt::While(while_stmt) => {
+                while_stmt.condition.accept::<()>(self);
+                while_stmt.body.accept::<()>(self);
+            }
+            Stmt::For(for_stmt) => {
+                self.scopes.borrow_mut().push(HashMap::new());
+                if let Some(initializer) = &for_stmt.initializer {
+                    initializer.accept::<()>(self);
+                }
+                if let Some(condition) = &for_stmt.condition {
+                    condition.accept::<()>(self);
+                }
+                if let Some(increment) = &for_stmt.increment {
+                    increment.accept::<()>(self);
+                }
+                for_stmt.body.accept::<()>(self);
+                self.scopes.borrow_mut().pop();
+            }
+            // The variable is declared in a scope of its own, around the body.
+            Stmt::ForIn(for_in) => {
+                for_in.iterable.accept::<()>(self);
+                self.scopes.borrow_mut().push(HashMap::new());
+                self.declare(&for_in.name);
+                self.resolve(&for_in.name);
+                for_in.body.accept::<()>(self);
+                self.scopes.borrow_mut().pop();
+            }
+            Stmt::Function(function) => {
+                // Declared first, so the body can call itself.
+                self.declare(&function.name);
+                self.resolve(&function.name);
+
+                self.scopes.borrow_mut().push(HashMap::new());
+                for param in &function.params {
+                    self.declare(param);
+                    self.resolve(param);
+                }
+                self.statements(&function.body);
+                self.scopes.borrow_mut().pop();
+            }
+            Stmt::Return(return_stmt) => {
+                if let Some(value) = &return_stmt.value {
+                    value.accept::<()>(self);
+                }
+            }
+            Stmt::Throw(throw) => throw.value.accept::<()>(self),
+            // Each block is a scope, the error is declared in the catch's.
+            Stmt::Try(try_stmt) => {
+                self.scopes.borrow_mut().push(HashMap::new());
+                self.statements(&try_stmt.body.statements);
+                self.scopes.borrow_mut().pop();
+                if let Some((name, catch)) = &try_stmt.catch {
+                    self.scopes.borrow_mut().push(HashMap::new());
+                    self.declare(name);
+                    self.resolve(name);
+                    self.statements(&catch.statements);
+                    self.scopes.borrow_mut().pop();
+                }
+                if let Some(finally) = &try_stmt.finally {
+                    self.scopes.borrow_mut().push(HashMap::new());
+                    self.statements(&finally.statements);
+                    self.scopes.borrow_mut().pop();
+                }
+            }
+            // Labels are not variables.
+            Stmt::Break(_) | Stmt::Continue(_) => (),
+            Stmt::Import(import) => {
+                for name in import.alias.iter().chain(import.names.iter()) {
+                    self.declare(name);
+                    self.resolve(name);
+                }
+            }
+            Stmt::Test(test) => {
+                self.scopes.borrow_mut().push(HashMap::new());
+                self.statements(&test.body);
+                self.scopes.borrow_mut().pop();
+            }
+        }
+    }
+}
+
+impl Visitor<Expr, ()> for Resolver {
+    fn visit(&self, expr: &Expr) {
+        match expr {
+            Expr::Literal(_) => (),
+            Expr::Grouping(grouping) => grouping.expression.accept::<()>(self),
+            Expr::Unary(unary) => unary.right.accept::<()>(self),
+            Expr::Binary(binary) => {
+                binary.left.accept::<()>(self);
+                binary.right.accept::<()>(self);
+            }
+            Expr::Variable(variable) => self.resolve(&variable.name),
+            Expr::Assign(assign) => {
+                assign.value.accept::<()>(self);
+                self.resolve(&assign.name);
+            }
+            Expr::Logical(logical) => {
+                logical.left.accept::<()>(self);
+                logical.right.accept::<()>(self);
+            }
+            Expr::Conditional(conditional) => {
+                conditional.condition.accept::<()>(self);
+                conditional.then_branch.accept::<()>(self);
+                conditional.else_branch.accept::<()>(self);
+            }
+            Expr::Comma(comma) => {
+                comma.left.accept::<()>(self);
+                comma.right.accept::<()>(self);
+            }
+            Expr::Call(call) => {
+                call.callee.accept::<()>(self);
+                for argument in &call.arguments {
+                    argument.accept::<()>(self);
+                }
+            }
+            Expr::Interpolation(interpolation) => {
+                for part in &interpolation.parts {
+                    part.accept::<()>(self);
+                }
+            }
+            Expr::List(list) => {
+                for element in &list.elements {
+                    element.accept::<()>(self);
+                }
+            }
+            Expr::Map(map) => {
+                for (key, value) in &map.entries {
+                    key.accept::<()>(self);
+                    value.accept::<()>(self);
+                }
+            }
+            Expr::Range(range) => {
+                range.start.accept::<()>(self);
+                range.end.accept::<()>(self);
+            }
+            Expr::Get(get) => get.object.accept::<()>(self),
+            Expr::Index(index) => {
+                index.object.accept::<()>(self);
+                index.index.accept::<()>(self);
+            }
+            Expr::SetIndex(set_index) => {
+                set_index.object.accept::<()>(self);
+                set_index.index.accept::<()>(self);
+                set_index.value.accept::<()>(self);
+            }
+        }
+    }
+}
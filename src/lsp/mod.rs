@@ -0,0 +1,6 @@
+pub mod analysis;
+pub mod server;
+pub mod transport;
+
+pub use analysis::Document;
+pub use server::LanguageServer;
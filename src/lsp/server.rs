@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use serde_json::{json, Value};
+
+use super::analysis::{Document, Position, Range, SymbolKind, SEMANTIC_TOKEN_TYPES};
+use super::transport;
+
+// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+// Speaks the Language Server Protocol over any reader/writer pair, normally stdin and stdout.
+#[derive(Default)]
+pub struct LanguageServer {
+    documents: HashMap<String, Document>,
+    shutdown_requested: bool,
+}
+
+impl LanguageServer {
+    pub fn new() -> LanguageServer {
+        return Self::default();
+    }
+
+    // Serves until the client sends `exit` or closes the input. Returns the exit code the spec
+    // asks for: 0 after a proper shutdown, 1 otherwise.
+    pub fn run<R: BufRead, W: Write>(&mut self, reader: &mut R, writer: &mut W) -> io::Result<i32> {
+        while let Some(body) = transport::read_body(reader)? {
+            let message: Value = match serde_json::from_slice(&body) {
+                Ok(message) => message,
+                // The id cannot be read, so the reply has none.
+                Err(err) => {
+                    let response: Value = json!({
+                        "jsonrpc": "2.0",
+                        "id": Value::Null,
+                        "error": { "code": PARSE_ERROR, "message": err.to_string() },
+                    });
+                    transport::write_message(writer, &response)?;
+                    continue;
+                }
+            };
+            let method: &str = message["method"].as_str().unwrap_or_default();
+            let params: &Value = &message["params"];
+
+            if method == "exit" {
+                break;
+            }
+
+            match message.get("id") {
+                Some(id) => {
+                    let response: Value = match self.request(method, params) {
+                        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                        Err((code, error)) => json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": { "code": code, "message": error },
+                        }),
+                    };
+                    transport::write_message(writer, &response)?;
+                }
+                None => {
+                    for notification in self.notification(method, params) {
+                        transport::write_message(writer, &notification)?;
+                    }
+                }
+            }
+        }
+
+        return match self.shutdown_requested {
+            true => Ok(0),
+            false => Ok(1),
+        };
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        return match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "documentSymbolProvider": true,
+                    "definitionProvider": true,
+                    "semanticTokensProvider": {
+                        "legend": { "tokenTypes": SEMANTIC_TOKEN_TYPES, "tokenModifiers": [] },
+                        "full": true,
+                    },
+                },
+                "serverInfo": { "name": "rlox", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => {
+                let (document, position) = self.document_position(params)?;
+                Ok(match document.hover(position) {
+                    Some((range, text)) => json!({
+                        "contents": { "kind": "plaintext", "value": text },
+                        "range": Self::range(range),
+                    }),
+                    None => Value::Null,
+                })
+            }
+            "textDocument/definition" => {
+                let uri: String = Self::uri(params)?;
+                let (document, position) = self.document_position(params)?;
+                Ok(match document.definition(position) {
+                    Some(range) => json!({ "uri": uri, "range": Self::range(range) }),
+                    None => Value::Null,
+                })
+            }
+            "textDocument/documentSymbol" => {
+                let document: &Document = self.document(params)?;
+                let symbols: Vec<Value> = document
+                    .symbols()
+                    .into_iter()
+                    .map(|symbol| {
+                        json!({
+                            "name": symbol.name,
+                            "kind": match symbol.kind {
+                                SymbolKind::Variable => 13,
+                                SymbolKind::Function => 12,
+                            },
+                            "range": Self::range(symbol.range),
+                            "selectionRange": Self::range(symbol.selection_range),
+                        })
+                    })
+                    .collect();
+                Ok(Value::from(symbols))
+            }
+            "textDocument/semanticTokens/full" => {
+                let document: &Document = self.document(params)?;
+                Ok(json!({ "data": document.semantic_tokens() }))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unhandled method '{}'.", method))),
+        };
+    }
+
+    // Handles a notification, returning the notifications to send back.
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri: String = match Self::uri(params) {
+            Ok(uri) => uri,
+            Err(_) => return Vec::new(),
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let text: &str = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents
+                    .insert(uri.clone(), Document::new(String::from(text)));
+            }
+            "textDocument/didChange" => {
+                // Full sync: the last change holds the whole text.
+                let changes: &Vec<Value> = match params["contentChanges"].as_array() {
+                    Some(changes) => changes,
+                    None => return Vec::new(),
+                };
+                if let Some(text) = changes.last().and_then(|change| change["text"].as_str()) {
+                    self.documents
+                        .insert(uri.clone(), Document::new(String::from(text)));
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+            }
+            _ => return Vec::new(),
+        }
+
+        return vec![self.diagnostics(&uri)];
+    }
+
+    fn diagnostics(&self, uri: &str) -> Value {
+        let diagnostics: Vec<Value> = match self.documents.get(uri) {
+            Some(document) => document
+                .get_errors()
+                .iter()
+                .map(|error| {
+                    json!({
+                        "range": Self::range(document.error_range(error)),
+                        // DiagnosticSeverity.Error
+                        "severity": 1,
+                        "source": "rlox",
+                        "message": error.get_message(),
+                    })
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        return json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        });
+    }
+
+    fn document(&self, params: &Value) -> Result<&Document, (i64, String)> {
+        let uri: String = Self::uri(params)?;
+
+        return match self.documents.get(&uri) {
+            Some(document) => Ok(document),
+            None => Err((INVALID_PARAMS, format!("Unknown document '{}'.", uri))),
+        };
+    }
+
+    fn document_position(&self, params: &Value) -> Result<(&Document, Position), (i64, String)> {
+        let position: &Value = &params["position"];
+
+        return match (position["line"].as_u64(), position["character"].as_u64()) {
+            (Some(line), Some(character)) => Ok((
+                self.document(params)?,
+                Position {
+                    line: line as u32,
+                    character: character as u32,
+                },
+            )),
+            _ => Err((INVALID_PARAMS, String::from("Missing position."))),
+        };
+    }
+
+    fn uri(params: &Value) -> Result<String, (i64, String)> {
+        return match params["textDocument"]["uri"].as_str() {
+            Some(uri) => Ok(String::from(uri)),
+            None => Err((INVALID_PARAMS, String::from("Missing text document."))),
+        };
+    }
+
+    fn range(range: Range) -> Value {
+        return json!({
+            "start": { "line": range.start.line, "character": range.start.character },
+            "end": { "line": range.end.line, "character": range.end.character },
+        });
+    }
+}
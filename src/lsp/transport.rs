@@ -0,0 +1,77 @@
+use std::io::{self, BufRead, Read, Write};
+
+use serde_json::Value;
+
+// Base protocol framing: a `Content-Length` header, a blank line, then that many bytes of JSON.
+
+// Reads the next message, or None once the input is closed.
+pub fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<Value>> {
+    let body: Vec<u8> = match read_body(reader)? {
+        Some(body) => body,
+        None => return Ok(None),
+    };
+
+    return match serde_json::from_slice(&body) {
+        Ok(message) => Ok(Some(message)),
+        Err(err) => Err(invalid_data(&err.to_string())),
+    };
+}
+
+// Reads the bytes of the next message without parsing them, or None once the input is closed.
+// A body that is not JSON leaves the stream in sync, so the caller may carry on past it. Headers
+// without a valid Content-Length are skipped, since where their body ends is unknown, and a body
+// cut short by the end of the input counts as the input being closed.
+pub fn read_body<R: BufRead>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    loop {
+        let content_length: Option<usize> = match read_headers(reader)? {
+            Some(content_length) => content_length,
+            None => return Ok(None),
+        };
+        let length: usize = match content_length {
+            Some(length) => length,
+            None => continue,
+        };
+
+        // Read as it arrives rather than allocated up front, whatever length the header claims.
+        let mut body: Vec<u8> = Vec::new();
+        reader.take(length as u64).read_to_end(&mut body)?;
+        if body.len() < length {
+            return Ok(None);
+        }
+        return Ok(Some(body));
+    }
+}
+
+// The Content-Length of the next header block, None once the input is closed.
+fn read_headers<R: BufRead>(reader: &mut R) -> io::Result<Option<Option<usize>>> {
+    let mut content_length: Option<usize> = None;
+
+    loop {
+        let mut line: Vec<u8> = Vec::new();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let header = String::from_utf8_lossy(&line);
+        let header: &str = header.trim_end();
+        if header.is_empty() {
+            return Ok(Some(content_length));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+}
+
+pub fn write_message<W: Write>(writer: &mut W, message: &Value) -> io::Result<()> {
+    let body: String = message.to_string();
+
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    return writer.flush();
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+}
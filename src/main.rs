@@ -1,36 +1,89 @@
-mod interpreter;
-mod lexer;
-mod parser;
-mod utils;
+#![allow(clippy::needless_return)]
 
-use interpreter::Interpreter;
-use lexer::{Scanner, Token};
-use parser::{AstPrinter, Expr, Parser, Stmt};
-use std::{env, fs, io, io::Write, process};
+use rlox::dap::DebugAdapter;
+use rlox::debugger::PromptDebugger;
+use rlox::interpreter::Interpreter;
+use rlox::lexer::{Scanner, Token};
+use rlox::lsp::LanguageServer;
+use rlox::parser::{Formatter, Parser, Stmt};
+use rlox::testing::{TestResult, TestRunner};
+use std::path::{Path, PathBuf};
+use std::{env, fs, io, io::Write, process, thread};
+
+// Stack of the thread everything runs on. Parsing a program nested as deep as the parser allows
+// takes more than the 8 MiB stack of the main thread in debug builds, and the interpreter lets
+// recursion use half of it (see Interpreter::set_stack_size).
+const STACK_SIZE: usize = 64 * 1024 * 1024;
 
 fn main() {
-    let mut args: env::Args = env::args();
-    args.next();
+    let cli: thread::JoinHandle<()> =
+        match thread::Builder::new().stack_size(STACK_SIZE).spawn(run_cli) {
+            Ok(cli) => cli,
+            Err(err) => {
+                eprintln!("Error: cannot start the interpreter thread: {}", err);
+                process::exit(71);
+            }
+        };
+    if cli.join().is_err() {
+        process::exit(101);
+    }
+}
+
+fn run_cli() {
+    let args: Vec<String> = env::args().skip(1).collect();
 
     // Global Error state
     let mut had_err: bool = false;
     let mut had_runtime_err: bool = false;
 
-    match args.len() {
-        0 => run_prompt(&mut had_err, &mut had_runtime_err),
-        1 => run_file(&args.next().unwrap(), &mut had_err, &mut had_runtime_err),
+    let result: Result<(), io::Error> = match args.first().map(String::as_str) {
+        Some("fmt") => run_fmt(&args[1..]),
+        Some("test") => run_test(&args[1..]),
+        Some("lsp") if args.len() == 1 => run_lsp(),
+        Some("dap") if args.len() == 1 => run_dap(),
+        Some("debug") if args.len() == 2 => run_debug(&args[1], &mut had_err, &mut had_runtime_err),
+        _ => run_script(&args, &mut had_err, &mut had_runtime_err),
+    };
+
+    // Like the other errors, reported with a sysexits code rather than a panic: 66 (EX_NOINPUT)
+    // when a file is missing or unreadable, 74 (EX_IOERR) for any other I/O error.
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        process::exit(match err.kind() {
+            io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied => 66,
+            _ => 74,
+        });
+    }
+}
+
+fn run_script(
+    args: &[String],
+    had_err: &mut bool,
+    had_runtime_err: &mut bool,
+) -> Result<(), io::Error> {
+    return match args.len() {
+        0 => run_prompt(had_err, had_runtime_err),
+        1 => run_file(&args[0], had_err, had_runtime_err),
         _ => {
             eprintln!("Usage: rlox [script]");
+            eprintln!("       rlox debug <script>");
+            eprintln!("       rlox fmt [--check] <path>...");
+            eprintln!("       rlox test <path>...");
+            eprintln!("       rlox lsp");
+            eprintln!("       rlox dap");
             process::exit(64);
         }
-    }
-    .expect("");
+    };
 }
 
 fn run_file(path: &str, had_err: &mut bool, had_runtime_err: &mut bool) -> Result<(), io::Error> {
-    let source: String = fs::read_to_string(path)?;
+    let source: String = read_file(path)?;
+    let mut interpreter: Interpreter = Interpreter::new(had_runtime_err);
+    interpreter.set_stack_size(STACK_SIZE);
+    interpreter.set_file(path);
+    interpreter.set_search_paths(search_paths());
 
-    run(source, had_err, had_runtime_err);
+    run(source, had_err, &mut interpreter);
     if *had_err {
         process::exit(65);
     }
@@ -40,8 +93,21 @@ fn run_file(path: &str, had_err: &mut bool, had_runtime_err: &mut bool) -> Resul
     Ok(())
 }
 
+// Where imports are looked for besides next to the importing file: the directories listed in
+// RLOX_PATH, separated as in PATH.
+fn search_paths() -> Vec<PathBuf> {
+    return match env::var_os("RLOX_PATH") {
+        Some(paths) => env::split_paths(&paths).collect(),
+        None => Vec::new(),
+    };
+}
+
 fn run_prompt(had_err: &mut bool, had_runtime_err: &mut bool) -> Result<(), io::Error> {
     let mut prompt = String::new();
+    // One interpreter for the whole session so variables outlive the line that declared them.
+    let mut interpreter: Interpreter = Interpreter::new(had_runtime_err);
+    interpreter.set_stack_size(STACK_SIZE);
+    interpreter.set_search_paths(search_paths());
 
     loop {
         print!(">>> ");
@@ -55,7 +121,7 @@ fn run_prompt(had_err: &mut bool, had_runtime_err: &mut bool) -> Result<(), io::
         if nbytes == 0 {
             break;
         }
-        run(prompt, had_err, had_runtime_err);
+        run(prompt, had_err, &mut interpreter);
         *had_err = false;
 
         prompt = String::new();
@@ -63,7 +129,7 @@ fn run_prompt(had_err: &mut bool, had_runtime_err: &mut bool) -> Result<(), io::
     Ok(())
 }
 
-fn run(source: String, had_err: &mut bool, had_runtime_err: &mut bool) {
+fn run(source: String, had_err: &mut bool, interpreter: &mut Interpreter) {
     let mut scanner: Scanner = Scanner::new(&source);
     let tokens: &Vec<Token> = scanner.scan_tokens(had_err);
     let mut parser: Parser = Parser::new(tokens.clone(), had_err);
@@ -73,15 +139,212 @@ fn run(source: String, had_err: &mut bool, had_runtime_err: &mut bool) {
         return;
     }
 
-    let myprinter: AstPrinter = AstPrinter {};
-    let mut interpreter: Interpreter = Interpreter::new(had_runtime_err);
+    interpreter.interpret(statements);
+}
+
+// Runs a script under the command line debugger, reading commands from stdin.
+fn run_debug(path: &str, had_err: &mut bool, had_runtime_err: &mut bool) -> Result<(), io::Error> {
+    let source: String = read_file(path)?;
+    let mut scanner: Scanner = Scanner::new(&source);
+    let tokens: Vec<Token> = scanner.scan_tokens(had_err).clone();
+    let mut parser: Parser = Parser::new(tokens, had_err);
+    let statements: Vec<Stmt> = parser.parse();
+
+    if *had_err {
+        process::exit(65);
+    }
 
+    let mut interpreter: Interpreter = Interpreter::new(had_runtime_err);
+    interpreter.set_stack_size(STACK_SIZE);
+    interpreter.set_file(path);
+    interpreter.set_search_paths(search_paths());
+    interpreter.set_debugger(Box::new(PromptDebugger::new(
+        &source,
+        Box::new(io::BufReader::new(io::stdin())),
+        Box::new(io::stdout()),
+    )));
     interpreter.interpret(statements);
-    // match expression {
-    //     Some(expr) => {
-    //         println!("{}", myprinter.print(&expr));
-    //         interpreter.interpret(expr);
-    //     }
-    //     None => println!("nothing"),
-    // };
+
+    if *had_runtime_err {
+        process::exit(70);
+    }
+    Ok(())
+}
+
+// Serves the Debug Adapter Protocol on stdin/stdout for editors.
+fn run_dap() -> Result<(), io::Error> {
+    let mut adapter: DebugAdapter = DebugAdapter::new(
+        Box::new(io::BufReader::new(io::stdin())),
+        Box::new(io::stdout()),
+    );
+    adapter.set_stack_size(STACK_SIZE);
+    return adapter.run();
+}
+
+// Serves the Language Server Protocol on stdin/stdout for editors.
+fn run_lsp() -> Result<(), io::Error> {
+    let mut server: LanguageServer = LanguageServer::new();
+    let exit_code: i32 = server.run(&mut io::stdin().lock(), &mut io::stdout().lock())?;
+    process::exit(exit_code);
+}
+
+// Rewrites every given file (or .lox file under a given directory) in canonical form.
+// With --check nothing is written; files that are not formatted are listed and the exit code is 1.
+fn run_fmt(args: &[String]) -> Result<(), io::Error> {
+    let check: bool = args.iter().any(|arg| arg == "--check");
+    let mut paths: Vec<String> = Vec::new();
+    for arg in args.iter().filter(|arg| *arg != "--check") {
+        collect_lox_files(Path::new(arg), &mut paths)?;
+    }
+
+    if paths.is_empty() {
+        eprintln!("Usage: rlox fmt [--check] <path>...");
+        process::exit(64);
+    }
+
+    let mut had_err: bool = false;
+    let mut unformatted: bool = false;
+
+    for path in paths {
+        let source: String = read_file(&path)?;
+        let mut file_err: bool = false;
+        let formatted: String = format_source(&source, &mut file_err);
+
+        if file_err {
+            eprintln!("{}: not formatted because of syntax errors", path);
+            had_err = true;
+        } else if formatted != source {
+            if check {
+                println!("{}", path);
+                unformatted = true;
+            } else {
+                fs::write(&path, formatted).map_err(|err| file_error(&path, err))?;
+            }
+        }
+    }
+
+    if had_err {
+        process::exit(65);
+    }
+    if unformatted {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+// Runs the tests of every given file (or .lox file under a given directory), see TestRunner.
+// The exit code is 1 when a test failed and 65 when a file has syntax errors.
+fn run_test(args: &[String]) -> Result<(), io::Error> {
+    let mut paths: Vec<String> = Vec::new();
+    for arg in args {
+        collect_lox_files(Path::new(arg), &mut paths)?;
+    }
+
+    if paths.is_empty() {
+        eprintln!("Usage: rlox test <path>...");
+        process::exit(64);
+    }
+
+    let mut had_err: bool = false;
+    let mut failures: Vec<(String, TestResult)> = Vec::new();
+    let mut passed: usize = 0;
+
+    for path in paths {
+        let source: String = read_file(&path)?;
+        let mut file_err: bool = false;
+        let mut scanner: Scanner = Scanner::new(&source);
+        let tokens: Vec<Token> = scanner.scan_tokens(&mut file_err).clone();
+        let mut parser: Parser = Parser::new(tokens, &mut file_err);
+        let statements: Vec<Stmt> = parser.parse();
+
+        if file_err {
+            eprintln!("{}: not tested because of syntax errors", path);
+            had_err = true;
+            continue;
+        }
+
+        let mut runner: TestRunner = TestRunner::new(statements);
+        runner.set_stack_size(STACK_SIZE);
+        for result in runner.run() {
+            match result.passed() {
+                true => {
+                    println!("test {}: {} ... ok", path, result.get_name());
+                    passed += 1;
+                }
+                false => {
+                    println!("test {}: {} ... FAILED", path, result.get_name());
+                    failures.push((path.clone(), result));
+                }
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        println!();
+        println!("failures:");
+        for (path, result) in &failures {
+            println!();
+            println!("---- {}: {} ----", path, result.get_name());
+            print!("{}", result.get_output());
+            println!("{}", result.get_failure().unwrap_or_default());
+        }
+    }
+
+    let status: &str = match failures.is_empty() {
+        true => "ok",
+        false => "FAILED",
+    };
+    println!();
+    println!(
+        "test result: {}. {} passed; {} failed",
+        status,
+        passed,
+        failures.len()
+    );
+
+    if had_err {
+        process::exit(65);
+    }
+    if !failures.is_empty() {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn collect_lox_files(path: &Path, paths: &mut Vec<String>) -> Result<(), io::Error> {
+    if !path.is_dir() {
+        paths.push(path.to_string_lossy().into_owned());
+        return Ok(());
+    }
+
+    let mut entries: Vec<_> = fs::read_dir(path)
+        .and_then(|entries| entries.collect::<Result<_, _>>())
+        .map_err(|err| file_error(&path.to_string_lossy(), err))?;
+    entries.sort_by_key(|entry| entry.path());
+    for entry in entries {
+        let entry_path = entry.path();
+        if entry_path.is_dir() || entry_path.extension().is_some_and(|ext| ext == "lox") {
+            collect_lox_files(&entry_path, paths)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_file(path: &str) -> Result<String, io::Error> {
+    return fs::read_to_string(path).map_err(|err| file_error(path, err));
+}
+
+// io::Error leaves out the path of the file it is about.
+fn file_error(path: &str, err: io::Error) -> io::Error {
+    return io::Error::new(err.kind(), format!("{}: {}", path, err));
+}
+
+fn format_source(source: &str, had_err: &mut bool) -> String {
+    let mut scanner: Scanner = Scanner::new(source);
+    let tokens: Vec<Token> = scanner.scan_tokens(had_err).clone();
+    let mut parser: Parser = Parser::new(tokens, had_err);
+    let statements: Vec<Stmt> = parser.parse();
+
+    let formatter: Formatter = Formatter::new(scanner.get_comments());
+    return formatter.format(&statements);
 }
@@ -21,18 +21,143 @@ ast!(
 pub enum Stmt {
     Expression : struct {
         pub expression: Box<Expr>,
+        pub lines: Lines,
     },
     Print : struct {
         pub expression: Box<Expr>,
+        pub lines: Lines,
+    },
+    Var : struct {
+        pub name: Token,
+        pub initializer: Option<Box<Expr>>,
+        pub lines: Lines,
+    },
+    Block : struct {
+        pub statements: Vec<Stmt>,
+        pub lines: Lines,
+    },
+    If : struct {
+        pub condition: Box<Expr>,
+        pub then_branch: Box<Stmt>,
+        pub else_branch: Option<Box<Stmt>>,
+        pub lines: Lines,
+    },
+    While : struct {
+        // label: while (...)
+        pub label: Option<Token>,
+        pub condition: Box<Expr>,
+        pub body: Box<Stmt>,
+        pub lines: Lines,
+    },
+    For : struct {
+        pub label: Option<Token>,
+        pub initializer: Option<Box<Stmt>>,
+        pub condition: Option<Box<Expr>>,
+        pub increment: Option<Box<Expr>>,
+        pub body: Box<Stmt>,
+        pub lines: Lines,
+    },
+    // for (name in iterable) body
+    ForIn : struct {
+        pub label: Option<Token>,
+        pub name: Token,
+        pub iterable: Box<Expr>,
+        pub body: Box<Stmt>,
+        pub lines: Lines,
+    },
+    // break label?;
+    Break : struct {
+        pub keyword: Token,
+        pub label: Option<Token>,
+        pub lines: Lines,
+    },
+    // continue label?;
+    Continue : struct {
+        pub keyword: Token,
+        pub label: Option<Token>,
+        pub lines: Lines,
+    },
+    // throw value;
+    Throw : struct {
+        pub keyword: Token,
+        pub value: Box<Expr>,
+        pub lines: Lines,
+    },
+    // try body catch (name) body finally body, with a catch, a finally or both.
+    Try : struct {
+        pub body: Block,
+        pub catch: Option<(Token, Block)>,
+        pub finally: Option<Block>,
+        pub lines: Lines,
+    },
+    // import "path" as alias; or import { names } from "path";
+    Import : struct {
+        pub keyword: Token,
+        // the string token, where errors loading the module are reported.
+        pub path: Token,
+        // set for the first form, names for the second.
+        pub alias: Option<Token>,
+        pub names: Vec<Token>,
+        pub lines: Lines,
+    },
+    Function : struct {
+        pub name: Token,
+        pub params: Vec<Token>,
+        pub body: Vec<Stmt>,
+        pub lines: Lines,
+    },
+    Return : struct {
+        pub keyword: Token,
+        pub value: Option<Box<Expr>>,
+        pub lines: Lines,
+    },
+    // Only run by `rlox test`.
+    Test : struct {
+        // the string literal naming the test.
+        pub name: Token,
+        pub body: Vec<Stmt>,
+        pub lines: Lines,
     },
 }
     );
 
+// First and last source line of a statement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lines {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl Stmt {
+    pub fn get_lines(&self) -> Lines {
+        return match self {
+            Stmt::Expression(expression) => expression.lines,
+            Stmt::Print(print) => print.lines,
+            Stmt::Var(var) => var.lines,
+            Stmt::Block(block) => block.lines,
+            Stmt::If(if_stmt) => if_stmt.lines,
+            Stmt::While(while_stmt) => while_stmt.lines,
+            Stmt::For(for_stmt) => for_stmt.lines,
+            Stmt::ForIn(for_in) => for_in.lines,
+            Stmt::Break(break_stmt) => break_stmt.lines,
+            Stmt::Continue(continue_stmt) => continue_stmt.lines,
+            Stmt::Throw(throw) => throw.lines,
+            Stmt::Try(try_stmt) => try_stmt.lines,
+            Stmt::Function(function) => function.lines,
+            Stmt::Import(import) => import.lines,
+            Stmt::Return(return_stmt) => return_stmt.lines,
+            Stmt::Test(test) => test.lines,
+        };
+    }
+}
+
 ast!(
 pub enum Expr {
     Literal : enum {
-        Number(f32),
-        String(String),
+        // lexeme is the literal as written: the base, separators and escapes are kept.
+        Number { value: f32, lexeme: String },
+        Integer { value: i64, lexeme: String },
+        String { value: String, lexeme: String },
         True,
         False,
         Nil
@@ -49,6 +174,77 @@ pub enum Expr {
         pub right: Box<Expr>,
         pub operator: Token,
     },
+    Variable : struct {
+        pub name: Token,
+    },
+    Assign : struct {
+        pub name: Token,
+        // "=" or a compound assignment such as "+=".
+        pub operator: Token,
+        pub value: Box<Expr>,
+    },
+    // cond ? a : b, only the branch taken is evaluated.
+    Conditional : struct {
+        pub condition: Box<Expr>,
+        pub then_branch: Box<Expr>,
+        pub else_branch: Box<Expr>,
+    },
+    // a, b evaluates both and gives b.
+    Comma : struct {
+        pub left: Box<Expr>,
+        pub right: Box<Expr>,
+    },
+    Logical : struct {
+        pub left: Box<Expr>,
+        pub operator: Token,
+        pub right: Box<Expr>,
+    },
+    Call : struct {
+        pub callee: Box<Expr>,
+        // closing parenthesis, used to report errors at the call.
+        pub paren: Token,
+        pub arguments: Vec<Expr>,
+    },
+    List : struct {
+        pub elements: Vec<Expr>,
+    },
+    // {key: value, ...}
+    Map : struct {
+        // closing brace, used to report errors in the keys.
+        pub brace: Token,
+        pub entries: Vec<(Expr, Expr)>,
+    },
+    // start..end or start..=end
+    Range : struct {
+        pub start: Box<Expr>,
+        pub operator: Token,
+        pub end: Box<Expr>,
+    },
+    // object.name, a method of a string.
+    Get : struct {
+        pub object: Box<Expr>,
+        pub name: Token,
+    },
+    // object[index]
+    Index : struct {
+        pub object: Box<Expr>,
+        // closing bracket, used to report errors at the index.
+        pub bracket: Token,
+        pub index: Box<Expr>,
+    },
+    // object[index] = value, or a compound assignment such as +=.
+    SetIndex : struct {
+        pub object: Box<Expr>,
+        pub bracket: Token,
+        pub index: Box<Expr>,
+        pub operator: Token,
+        pub value: Box<Expr>,
+    },
+    // "a ${b} c": string literals as written, "${" and "}" included, alternating with the
+    // expressions between them. Starts and ends with a literal.
+    Interpolation : struct {
+        pub parts: Vec<Expr>,
+    },
 }
     );
 
@@ -28,8 +28,9 @@ impl Visitor<Expr, String> for AstPrinter {
                 Literal::Nil => String::from("nil"),
                 Literal::True => String::from("true"),
                 Literal::False => String::from("false"),
-                Literal::String(string) => string.clone(),
-                Literal::Number(number) => number.to_string(),
+                Literal::String { value, .. } => value.clone(),
+                Literal::Number { value, .. } => value.to_string(),
+                Literal::Integer { value, .. } => value.to_string(),
             },
             Expr::Binary(binary) => self.parenthesize(
                 binary.operator.get_lexeme(),
@@ -41,21 +42,62 @@ impl Visitor<Expr, String> for AstPrinter {
             Expr::Unary(unary) => {
                 self.parenthesize(unary.operator.get_lexeme(), vec![&unary.right])
             }
+            Expr::Variable(variable) => variable.name.get_lexeme(),
+            Expr::Assign(assign) => self.parenthesize(
+                format!(
+                    "{} {}",
+                    assign.operator.get_lexeme(),
+                    assign.name.get_lexeme()
+                ),
+                vec![&assign.value],
+            ),
+            Expr::Conditional(conditional) => self.parenthesize(
+                String::from("?:"),
+                vec![
+                    &conditional.condition,
+                    &conditional.then_branch,
+                    &conditional.else_branch,
+                ],
+            ),
+            Expr::Comma(comma) => {
+                self.parenthesize(String::from(","), vec![&comma.left, &comma.right])
+            }
+            Expr::Logical(logical) => self.parenthesize(
+                logical.operator.get_lexeme(),
+                vec![&logical.left, &logical.right],
+            ),
+            Expr::Call(call) => {
+                let mut exprs: Vec<&Expr> = vec![&call.callee];
+                exprs.extend(call.arguments.iter());
+                self.parenthesize(String::from("call"), exprs)
+            }
+            Expr::Interpolation(interpolation) => self.parenthesize(
+                String::from("interpolation"),
+                interpolation.parts.iter().collect(),
+            ),
+            Expr::List(list) => {
+                self.parenthesize(String::from("list"), list.elements.iter().collect())
+            }
+            Expr::Map(map) => self.parenthesize(
+                String::from("map"),
+                map.entries
+                    .iter()
+                    .flat_map(|(key, value)| [key, value])
+                    .collect(),
+            ),
+            Expr::Range(range) => {
+                self.parenthesize(range.operator.get_lexeme(), vec![&range.start, &range.end])
+            }
+            Expr::Get(get) => {
+                self.parenthesize(format!(". {}", get.name.get_lexeme()), vec![&get.object])
+            }
+            Expr::Index(index) => {
+                self.parenthesize(String::from("index"), vec![&index.object, &index.index])
+            }
+            Expr::SetIndex(set_index) => self.parenthesize(
+                format!("{} index", set_index.operator.get_lexeme()),
+                vec![&set_index.object, &set_index.index, &set_index.value],
+            ),
         }
     }
 }
-
-// implement test
-// let expr: ast::Expr = ast::Expr::Binary(ast::Binary {
-//     left: Box::new(ast::Expr::Unary(ast::Unary {
-//         operator: Token::new(lexer::token::TokenType::Minus, String::from("-"), 1),
-//         right: Box::new(ast::Expr::Literal(ast::Literal::Number(123.0))),
-//     })),
-//     operator: Token::new(lexer::token::TokenType::Star, String::from("*"), 1),
-//     right: Box::new(ast::Expr::Grouping(ast::Grouping {
-//         expression: Box::new(ast::Expr::Literal(ast::Literal::Number(45.67))),
-//     })),
-// });
-// println!("{}", ast_printer::AstPrinter.print(&expr));
-//
-// return;
@@ -0,0 +1,726 @@
+use crate::lexer::{Token, TokenType};
+
+// Concrete syntax tree: the shape of the program with every token kept, including punctuation,
+// trivia and anything that failed to parse. Built from a lossless token stream
+// (Scanner::new_lossless), `to_source` gives back the original text byte for byte.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyntaxKind {
+    Program,
+    FunDecl,
+    TestDecl,
+    VarDecl,
+    ImportDecl,
+    PrintStmt,
+    ExprStmt,
+    IfStmt,
+    WhileStmt,
+    ForStmt,
+    ForInStmt,
+    ReturnStmt,
+    BreakStmt,
+    ContinueStmt,
+    // A loop with its label.
+    LabeledStmt,
+    ThrowStmt,
+    TryStmt,
+    Block,
+    Assign,
+    Conditional,
+    Comma,
+    Logical,
+    Grouping,
+    Unary,
+    Binary,
+    Range,
+    Call,
+    Get,
+    Index,
+    Interpolation,
+    List,
+    Map,
+    Literal,
+    Variable,
+    // Tokens that do not fit the grammar where they appear.
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(Token),
+}
+
+impl SyntaxElement {
+    pub fn to_source(&self) -> String {
+        return match self {
+            Self::Node(node) => node.to_source(),
+            Self::Token(token) => token.to_source(),
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntaxNode {
+    kind: SyntaxKind,
+    children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    pub fn new(kind: SyntaxKind, children: Vec<SyntaxElement>) -> SyntaxNode {
+        return Self { kind, children };
+    }
+    pub fn get_kind(&self) -> SyntaxKind {
+        return self.kind;
+    }
+    pub fn get_children(&self) -> &Vec<SyntaxElement> {
+        return &self.children;
+    }
+    // All tokens under this node, in source order.
+    pub fn tokens(&self) -> Vec<&Token> {
+        let mut tokens: Vec<&Token> = Vec::new();
+
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+
+        return tokens;
+    }
+    pub fn to_source(&self) -> String {
+        return self
+            .children
+            .iter()
+            .map(|child| child.to_source())
+            .collect::<String>();
+    }
+}
+
+// Follows the same grammar as Parser, but never reports errors or drops tokens: whatever does not
+// fit ends up in an Error node.
+pub struct CstBuilder {
+    tokens: Vec<Token>,
+    current: usize,
+}
+
+impl CstBuilder {
+    pub fn new(tokens: Vec<Token>) -> CstBuilder {
+        return Self { tokens, current: 0 };
+    }
+
+    // program -> declaration* EOF ;
+    pub fn build(mut self) -> SyntaxNode {
+        let mut children: Vec<SyntaxElement> = Vec::new();
+
+        while !self.is_at_end() {
+            children.push(self.declaration());
+        }
+        if let Some(eof) = self.tokens.get(self.current) {
+            children.push(SyntaxElement::Token(eof.clone()));
+        }
+
+        return SyntaxNode::new(SyntaxKind::Program, children);
+    }
+
+    // declaration -> fun_decl | import_decl | test_decl | var_decl | statement ;
+    // var_decl -> "var" IDENTIFIER ( "=" expression )? ";" ;
+    fn declaration(&mut self) -> SyntaxElement {
+        if self.check(&[TokenType::Fun]) {
+            return self.function();
+        }
+        if self.check(&[TokenType::Import]) {
+            return self.import();
+        }
+        if self.check_test() {
+            return self.test();
+        }
+        if !self.check(&[TokenType::Var]) {
+            return self.statement();
+        }
+
+        let mut children: Vec<SyntaxElement> = vec![self.advance()];
+        if self.check_identifier() {
+            children.push(self.advance());
+        }
+        if self.check(&[TokenType::Equal]) {
+            children.push(self.advance());
+            children.push(self.expression());
+        }
+        if self.check(&[TokenType::Semicolon]) {
+            children.push(self.advance());
+        }
+
+        return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::VarDecl, children));
+    }
+
+    // import_decl -> "import" STRING "as" IDENTIFIER ";"
+    //              | "import" "{" IDENTIFIER ( "," IDENTIFIER )* "}" "from" STRING ";" ;
+    fn import(&mut self) -> SyntaxElement {
+        let mut children: Vec<SyntaxElement> = vec![self.advance()];
+        while self.check_identifier()
+            || self.check_string()
+            || self.check(&[
+                TokenType::LeftBrace,
+                TokenType::Comma,
+                TokenType::RightBrace,
+            ])
+        {
+            children.push(self.advance());
+        }
+        if self.check(&[TokenType::Semicolon]) {
+            children.push(self.advance());
+        }
+
+        return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::ImportDecl, children));
+    }
+
+    // fun_decl -> "fun" IDENTIFIER "(" parameters? ")" block ;
+    fn function(&mut self) -> SyntaxElement {
+        let mut children: Vec<SyntaxElement> = vec![self.advance()];
+        if self.check_identifier() {
+            children.push(self.advance());
+        }
+        if self.check(&[TokenType::LeftParen]) {
+            children.push(self.advance());
+            while self.check_identifier() || self.check(&[TokenType::Comma]) {
+                children.push(self.advance());
+            }
+            if self.check(&[TokenType::RightParen]) {
+                children.push(self.advance());
+            }
+        }
+        if self.check(&[TokenType::LeftBrace]) {
+            children.push(self.block());
+        }
+
+        return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::FunDecl, children));
+    }
+
+    // test_decl -> "test" STRING block ;
+    fn test(&mut self) -> SyntaxElement {
+        let mut children: Vec<SyntaxElement> = vec![self.advance(), self.advance()];
+        if self.check(&[TokenType::LeftBrace]) {
+            children.push(self.block());
+        }
+
+        return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::TestDecl, children));
+    }
+
+    // statement -> expr_stmt | for_stmt | if_stmt | print_stmt | return_stmt | while_stmt
+    //            | break_stmt | continue_stmt | labeled_stmt | throw_stmt | try_stmt | block ;
+    fn statement(&mut self) -> SyntaxElement {
+        if self.check(&[TokenType::LeftBrace]) && !self.check_map() {
+            return self.block();
+        }
+        if self.check(&[TokenType::If]) {
+            return self.if_statement();
+        }
+        if self.check(&[TokenType::While]) {
+            let mut children: Vec<SyntaxElement> = vec![self.advance()];
+            self.condition(&mut children);
+            children.push(self.statement());
+            return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::WhileStmt, children));
+        }
+        if self.check(&[TokenType::For]) {
+            return self.for_statement();
+        }
+
+        if self.check(&[TokenType::Return]) {
+            let mut children: Vec<SyntaxElement> = vec![self.advance()];
+            if !self.check(&[TokenType::Semicolon]) {
+                children.push(self.expression());
+            }
+            if self.check(&[TokenType::Semicolon]) {
+                children.push(self.advance());
+            }
+            return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::ReturnStmt, children));
+        }
+        if self.check(&[TokenType::Try]) {
+            return self.try_statement();
+        }
+        // throw_stmt -> "throw" expression ";" ;
+        if self.check(&[TokenType::Throw]) {
+            let mut children: Vec<SyntaxElement> = vec![self.advance(), self.expression()];
+            if self.check(&[TokenType::Semicolon]) {
+                children.push(self.advance());
+            }
+            return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::ThrowStmt, children));
+        }
+        // break_stmt -> "break" IDENTIFIER? ";" ;
+        // continue_stmt -> "continue" IDENTIFIER? ";" ;
+        if self.check(&[TokenType::Break, TokenType::Continue]) {
+            let kind: SyntaxKind = match self.check(&[TokenType::Break]) {
+                true => SyntaxKind::BreakStmt,
+                false => SyntaxKind::ContinueStmt,
+            };
+            let mut children: Vec<SyntaxElement> = vec![self.advance()];
+            if self.check_identifier() {
+                children.push(self.advance());
+            }
+            if self.check(&[TokenType::Semicolon]) {
+                children.push(self.advance());
+            }
+            return SyntaxElement::Node(SyntaxNode::new(kind, children));
+        }
+        // labeled_stmt -> IDENTIFIER ":" ( for_stmt | while_stmt ) ;
+        if self.check_identifier()
+            && self
+                .tokens
+                .get(self.current + 1)
+                .is_some_and(|next| *next.get_token_type() == TokenType::Colon)
+        {
+            let children: Vec<SyntaxElement> =
+                vec![self.advance(), self.advance(), self.statement()];
+            return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::LabeledStmt, children));
+        }
+
+        let mut children: Vec<SyntaxElement> = Vec::new();
+
+        let kind: SyntaxKind = match self.check(&[TokenType::Print]) {
+            true => {
+                children.push(self.advance());
+                SyntaxKind::PrintStmt
+            }
+            false => SyntaxKind::ExprStmt,
+        };
+        children.push(self.expression());
+        if self.check(&[TokenType::Semicolon]) {
+            children.push(self.advance());
+        }
+
+        return SyntaxElement::Node(SyntaxNode::new(kind, children));
+    }
+
+    // if_stmt -> "if" "(" expression ")" statement ( "else" statement )? ;
+    fn if_statement(&mut self) -> SyntaxElement {
+        let mut children: Vec<SyntaxElement> = vec![self.advance()];
+        self.condition(&mut children);
+        children.push(self.statement());
+        if self.check(&[TokenType::Else]) {
+            children.push(self.advance());
+            children.push(self.statement());
+        }
+
+        return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::IfStmt, children));
+    }
+
+    // for_stmt -> "for" "(" ( var_decl | expr_stmt | ";" ) expression? ";" expression? ")" statement ;
+    fn for_statement(&mut self) -> SyntaxElement {
+        let mut children: Vec<SyntaxElement> = vec![self.advance()];
+        if !self.check(&[TokenType::LeftParen]) {
+            children.push(self.statement());
+            return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::ForStmt, children));
+        }
+        children.push(self.advance());
+
+        // "for" "(" IDENTIFIER "in" expression ")" statement
+        if self.check_identifier()
+            && self
+                .tokens
+                .get(self.current + 1)
+                .is_some_and(|next| *next.get_token_type() == TokenType::In)
+        {
+            children.push(self.advance());
+            children.push(self.advance());
+            children.push(self.expression());
+            if self.check(&[TokenType::RightParen]) {
+                children.push(self.advance());
+            }
+            children.push(self.statement());
+            return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::ForInStmt, children));
+        }
+
+        if self.check(&[TokenType::Semicolon]) {
+            children.push(self.advance());
+        } else {
+            children.push(self.declaration());
+        }
+        if !self.check(&[TokenType::Semicolon]) {
+            children.push(self.expression());
+        }
+        if self.check(&[TokenType::Semicolon]) {
+            children.push(self.advance());
+        }
+        if !self.check(&[TokenType::RightParen]) {
+            children.push(self.expression());
+        }
+        if self.check(&[TokenType::RightParen]) {
+            children.push(self.advance());
+        }
+        children.push(self.statement());
+
+        return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::ForStmt, children));
+    }
+
+    // try_stmt -> "try" block ( "catch" "(" IDENTIFIER ")" block )? ( "finally" block )? ;
+    fn try_statement(&mut self) -> SyntaxElement {
+        let mut children: Vec<SyntaxElement> = vec![self.advance()];
+        self.clause_block(&mut children);
+        if self.check(&[TokenType::Catch]) {
+            children.push(self.advance());
+            if self.check(&[TokenType::LeftParen]) {
+                children.push(self.advance());
+            }
+            if self.check_identifier() {
+                children.push(self.advance());
+            }
+            if self.check(&[TokenType::RightParen]) {
+                children.push(self.advance());
+            }
+            self.clause_block(&mut children);
+        }
+        if self.check(&[TokenType::Finally]) {
+            children.push(self.advance());
+            self.clause_block(&mut children);
+        }
+
+        return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::TryStmt, children));
+    }
+
+    // The block of a try, catch or finally, if there is one.
+    fn clause_block(&mut self, children: &mut Vec<SyntaxElement>) {
+        if self.check(&[TokenType::LeftBrace]) {
+            children.push(self.block());
+        }
+    }
+
+    // "(" expression ")" after if and while.
+    fn condition(&mut self, children: &mut Vec<SyntaxElement>) {
+        if self.check(&[TokenType::LeftParen]) {
+            children.push(self.advance());
+        }
+        children.push(self.expression());
+        if self.check(&[TokenType::RightParen]) {
+            children.push(self.advance());
+        }
+    }
+
+    // block -> "{" declaration* "}" ;
+    fn block(&mut self) -> SyntaxElement {
+        let mut children: Vec<SyntaxElement> = vec![self.advance()];
+
+        while !self.check(&[TokenType::RightBrace]) && !self.is_at_end() {
+            children.push(self.declaration());
+        }
+        if self.check(&[TokenType::RightBrace]) {
+            children.push(self.advance());
+        }
+
+        return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::Block, children));
+    }
+
+    // expression -> comma ;
+    // comma -> assignment ( "," assignment )* ;
+    fn expression(&mut self) -> SyntaxElement {
+        let mut expr: SyntaxElement = self.assignment();
+
+        while self.check(&[TokenType::Comma]) {
+            let comma: SyntaxElement = self.advance();
+            let right: SyntaxElement = self.assignment();
+            expr =
+                SyntaxElement::Node(SyntaxNode::new(SyntaxKind::Comma, vec![expr, comma, right]));
+        }
+        return expr;
+    }
+
+    // assignment -> ( IDENTIFIER | call "[" expression "]" ) ( "=" | "+=" | "-=" | "*=" | "/=" | "%=" )
+    //               assignment | conditional ;
+    fn assignment(&mut self) -> SyntaxElement {
+        let target: SyntaxElement = self.conditional();
+
+        if !self.check(&[
+            TokenType::Equal,
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+            TokenType::PercentEqual,
+        ]) {
+            return target;
+        }
+        let equals: SyntaxElement = self.advance();
+        let value: SyntaxElement = self.assignment();
+        return SyntaxElement::Node(SyntaxNode::new(
+            SyntaxKind::Assign,
+            vec![target, equals, value],
+        ));
+    }
+
+    // conditional -> logic_or ( "?" expression ":" assignment )? ;
+    fn conditional(&mut self) -> SyntaxElement {
+        let condition: SyntaxElement = self.binary(0);
+
+        if !self.check(&[TokenType::Question]) {
+            return condition;
+        }
+        let mut children: Vec<SyntaxElement> = vec![condition, self.advance(), self.expression()];
+        if self.check(&[TokenType::Colon]) {
+            children.push(self.advance());
+            children.push(self.assignment());
+        }
+        return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::Conditional, children));
+    }
+
+    // logic_or, logic_and, equality, comparison, range, bit_or, bit_xor, bit_and, shift, term
+    // and factor, loosest first.
+    fn binary(&mut self, level: usize) -> SyntaxElement {
+        let operators: [&[TokenType]; 11] = [
+            &[TokenType::Or],
+            &[TokenType::And],
+            &[TokenType::BangEqual, TokenType::EqualEqual],
+            &[
+                TokenType::Greater,
+                TokenType::GreaterEqual,
+                TokenType::Less,
+                TokenType::LessEqual,
+            ],
+            &[TokenType::DotDot, TokenType::DotDotEqual],
+            &[TokenType::Pipe],
+            &[TokenType::Caret],
+            &[TokenType::Ampersand],
+            &[TokenType::LessLess, TokenType::GreaterGreater],
+            &[TokenType::Plus, TokenType::Minus],
+            &[TokenType::Star, TokenType::Slash, TokenType::Percent],
+        ];
+
+        if level == operators.len() {
+            return self.unary();
+        }
+
+        let kind: SyntaxKind = match level {
+            0 | 1 => SyntaxKind::Logical,
+            4 => SyntaxKind::Range,
+            _ => SyntaxKind::Binary,
+        };
+        let mut expr: SyntaxElement = self.binary(level + 1);
+        while self.check(operators[level]) {
+            let operator: SyntaxElement = self.advance();
+            let right: SyntaxElement = self.binary(level + 1);
+            expr = SyntaxElement::Node(SyntaxNode::new(kind, vec![expr, operator, right]));
+        }
+
+        return expr;
+    }
+
+    // unary -> ("!" | "-" | "~") unary | power ;
+    fn unary(&mut self) -> SyntaxElement {
+        if self.check(&[TokenType::Bang, TokenType::Minus, TokenType::Tilde]) {
+            let operator: SyntaxElement = self.advance();
+            let right: SyntaxElement = self.unary();
+            return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::Unary, vec![operator, right]));
+        }
+
+        return self.power();
+    }
+
+    // power -> call ( "**" unary )? ;
+    fn power(&mut self) -> SyntaxElement {
+        let base: SyntaxElement = self.call();
+
+        if !self.check(&[TokenType::StarStar]) {
+            return base;
+        }
+        let operator: SyntaxElement = self.advance();
+        let exponent: SyntaxElement = self.unary();
+        return SyntaxElement::Node(SyntaxNode::new(
+            SyntaxKind::Binary,
+            vec![base, operator, exponent],
+        ));
+    }
+
+    // call -> primary ( "(" arguments? ")" | "[" expression "]" | "." IDENTIFIER )* ;
+    fn call(&mut self) -> SyntaxElement {
+        let mut expr: SyntaxElement = self.primary();
+
+        loop {
+            if self.check(&[TokenType::LeftParen]) {
+                let mut children: Vec<SyntaxElement> = vec![expr, self.advance()];
+                self.elements(&mut children, &[TokenType::RightParen]);
+                expr = SyntaxElement::Node(SyntaxNode::new(SyntaxKind::Call, children));
+            } else if self.check(&[TokenType::LeftBracket]) {
+                let mut children: Vec<SyntaxElement> =
+                    vec![expr, self.advance(), self.expression()];
+                if self.check(&[TokenType::RightBracket]) {
+                    children.push(self.advance());
+                }
+                expr = SyntaxElement::Node(SyntaxNode::new(SyntaxKind::Index, children));
+            } else if self.check(&[TokenType::Dot]) {
+                let mut children: Vec<SyntaxElement> = vec![expr, self.advance()];
+                if self.check_identifier() {
+                    children.push(self.advance());
+                }
+                expr = SyntaxElement::Node(SyntaxNode::new(SyntaxKind::Get, children));
+            } else {
+                return expr;
+            }
+        }
+    }
+
+    // Comma separated assignments up to and including `end`, for arguments and list elements.
+    fn elements(&mut self, children: &mut Vec<SyntaxElement>, end: &[TokenType]) {
+        while !self.check(end) && !self.is_at_end() {
+            children.push(self.assignment());
+            if !self.check(&[TokenType::Comma]) {
+                break;
+            }
+            children.push(self.advance());
+        }
+        if self.check(end) {
+            children.push(self.advance());
+        }
+    }
+
+    // primary -> NUMBER | STRING | interpolation | list | map | "true" | "false" | "nil"
+    //         | "(" expression ")" | IDENTIFIER ;
+    fn primary(&mut self) -> SyntaxElement {
+        if self.is_at_end() {
+            return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::Error, Vec::new()));
+        }
+
+        let kind: SyntaxKind = match self.tokens[self.current].get_token_type() {
+            TokenType::False
+            | TokenType::True
+            | TokenType::Nil
+            | TokenType::String(_)
+            | TokenType::Number(_)
+            | TokenType::Integer(_) => SyntaxKind::Literal,
+            TokenType::Identifier(_) => SyntaxKind::Variable,
+            TokenType::LeftParen => {
+                let mut children: Vec<SyntaxElement> = vec![self.advance()];
+                children.push(self.expression());
+                if self.check(&[TokenType::RightParen]) {
+                    children.push(self.advance());
+                }
+                return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::Grouping, children));
+            }
+            TokenType::Interpolation(_) => return self.interpolation(),
+            // list -> "[" ( assignment ( "," assignment )* ","? )? "]" ;
+            TokenType::LeftBracket => {
+                let mut children: Vec<SyntaxElement> = vec![self.advance()];
+                self.elements(&mut children, &[TokenType::RightBracket]);
+                return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::List, children));
+            }
+            // map -> "{" ( entry ( "," entry )* ","? )? "}" ;
+            TokenType::LeftBrace => return self.map(),
+            _ => SyntaxKind::Error,
+        };
+
+        return SyntaxElement::Node(SyntaxNode::new(kind, vec![self.advance()]));
+    }
+
+    // interpolation -> INTERPOLATION expression ( INTERPOLATION expression )* STRING ;
+    fn interpolation(&mut self) -> SyntaxElement {
+        let mut children: Vec<SyntaxElement> = Vec::new();
+
+        loop {
+            let ended: bool = !self.check_interpolation();
+            children.push(self.advance());
+            if ended {
+                break;
+            }
+            children.push(self.expression());
+            if !self.check_interpolation() && !self.check_string() {
+                break;
+            }
+        }
+        return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::Interpolation, children));
+    }
+
+    // entry -> assignment ":" assignment ;
+    fn map(&mut self) -> SyntaxElement {
+        let mut children: Vec<SyntaxElement> = vec![self.advance()];
+
+        while !self.check(&[TokenType::RightBrace]) && !self.is_at_end() {
+            children.push(self.assignment());
+            if !self.check(&[TokenType::Colon]) {
+                break;
+            }
+            children.push(self.advance());
+            children.push(self.assignment());
+            if !self.check(&[TokenType::Comma]) {
+                break;
+            }
+            children.push(self.advance());
+        }
+        if self.check(&[TokenType::RightBrace]) {
+            children.push(self.advance());
+        }
+        return SyntaxElement::Node(SyntaxNode::new(SyntaxKind::Map, children));
+    }
+
+    fn check(&self, types: &[TokenType]) -> bool {
+        return !self.is_at_end() && types.contains(self.tokens[self.current].get_token_type());
+    }
+    fn check_identifier(&self) -> bool {
+        return !self.is_at_end()
+            && matches!(
+                self.tokens[self.current].get_token_type(),
+                TokenType::Identifier(_)
+            );
+    }
+    fn check_string(&self) -> bool {
+        return !self.is_at_end()
+            && matches!(
+                self.tokens[self.current].get_token_type(),
+                TokenType::String(_)
+            );
+    }
+    fn check_interpolation(&self) -> bool {
+        return !self.is_at_end()
+            && matches!(
+                self.tokens[self.current].get_token_type(),
+                TokenType::Interpolation(_)
+            );
+    }
+    // `test` followed by a string, see Parser::check_test.
+    fn check_test(&self) -> bool {
+        let is_test: bool = !self.is_at_end()
+            && *self.tokens[self.current].get_token_type()
+                == TokenType::Identifier(String::from("test"));
+        return match self.tokens.get(self.current + 1) {
+            Some(next) => is_test && matches!(next.get_token_type(), TokenType::String(_)),
+            None => false,
+        };
+    }
+    // "{" starting a map rather than a block, see Parser::check_map.
+    fn check_map(&self) -> bool {
+        let is_key = |token: &Token| {
+            matches!(
+                token.get_token_type(),
+                TokenType::String(_)
+                    | TokenType::Number(_)
+                    | TokenType::Integer(_)
+                    | TokenType::Identifier(_)
+                    | TokenType::True
+                    | TokenType::False
+                    | TokenType::Nil
+            )
+        };
+        // A labeled loop opening the block.
+        let is_loop: bool = self.tokens.get(self.current + 3).is_some_and(|token| {
+            matches!(token.get_token_type(), TokenType::For | TokenType::While)
+        });
+        return match (
+            self.tokens.get(self.current + 1),
+            self.tokens.get(self.current + 2),
+        ) {
+            (Some(key), Some(colon)) => {
+                is_key(key) && *colon.get_token_type() == TokenType::Colon && !is_loop
+            }
+            _ => false,
+        };
+    }
+    fn advance(&mut self) -> SyntaxElement {
+        let token: Token = self.tokens[self.current].clone();
+        self.current += 1;
+        return SyntaxElement::Token(token);
+    }
+    fn is_at_end(&self) -> bool {
+        return match self.tokens.get(self.current) {
+            Some(token) => *token.get_token_type() == TokenType::Eof,
+            None => true,
+        };
+    }
+}
@@ -0,0 +1,541 @@
+use std::cell::Cell;
+
+use super::ast::{Expr, Literal, Stmt, Visitor};
+use crate::lexer::{Comment, Token, TokenType};
+
+// Binding power of each expression form, loosest first. Mirrors the grammar in grammar.ebnf.
+const COMMA: u8 = 1;
+const ASSIGNMENT: u8 = 2;
+const CONDITIONAL: u8 = 3;
+const OR: u8 = 4;
+const AND: u8 = 5;
+const EQUALITY: u8 = 6;
+const COMPARISON: u8 = 7;
+const RANGE: u8 = 8;
+const BIT_OR: u8 = 9;
+const BIT_XOR: u8 = 10;
+const BIT_AND: u8 = 11;
+const SHIFT: u8 = 12;
+const TERM: u8 = 13;
+const FACTOR: u8 = 14;
+const UNARY: u8 = 15;
+const POWER: u8 = 16;
+const CALL: u8 = 17;
+const PRIMARY: u8 = 18;
+
+const INDENT: &str = "    ";
+
+// Prints a parsed program back as canonical Lox source.
+pub struct Formatter<'a> {
+    comments: &'a Vec<Comment>,
+    // index of the first comment not printed yet.
+    next_comment: Cell<usize>,
+    // last source line printed, 0 at the start of a block.
+    last_line: Cell<u32>,
+    // line of the "}" closing the block being printed, 0 at the top level.
+    block_end: Cell<u32>,
+    depth: Cell<usize>,
+}
+
+impl<'a> Formatter<'a> {
+    pub fn new(comments: &'a Vec<Comment>) -> Formatter<'a> {
+        return Self {
+            comments,
+            next_comment: Cell::new(0),
+            last_line: Cell::new(0),
+            block_end: Cell::new(0),
+            depth: Cell::new(0),
+        };
+    }
+
+    // Comments are put back next to the statements they annotated, using the source lines
+    // recorded on each statement. Blank lines between statements are kept, but never more than one.
+    pub fn format(&self, statements: &Vec<Stmt>) -> String {
+        self.next_comment.set(0);
+        self.last_line.set(0);
+        self.block_end.set(0);
+        self.depth.set(0);
+
+        let mut result: String = self.statements(statements);
+        result.push_str(&self.comments_before(u32::MAX));
+        return result;
+    }
+
+    fn statements(&self, statements: &Vec<Stmt>) -> String {
+        let mut result: String = String::new();
+
+        for (index, statement) in statements.iter().enumerate() {
+            let lines = statement.get_lines();
+            // A comment at the end of the line belongs to whatever comes last on it: a following
+            // statement, or the "}" of an enclosing block.
+            let followed_on: u32 = match statements.get(index + 1) {
+                Some(next) => next.get_lines().start,
+                None => self.block_end.get(),
+            };
+
+            result.push_str(&self.comments_before(lines.start));
+            result.push_str(&self.separator(lines.start));
+
+            // A block prints the comments inside it itself; any other statement spanning several
+            // lines gets them above it.
+            let text: String = statement.accept::<String>(self);
+            result.push_str(&self.comments_before(lines.end));
+
+            result.push_str(&self.indentation());
+            result.push_str(&text);
+            let mut end: u32 = lines.end;
+            if let Some(comment) = self.comments.get(self.next_comment.get()) {
+                if comment.get_line() == lines.end && followed_on != lines.end {
+                    result.push(' ');
+                    result.push_str(comment.get_text());
+                    self.next_comment.set(self.next_comment.get() + 1);
+                    end = comment.get_end_line();
+                }
+            }
+            result.push('\n');
+            self.last_line.set(end);
+        }
+
+        return result;
+    }
+
+    // Prints, one per line, the pending comments that start before `line`.
+    fn comments_before(&self, line: u32) -> String {
+        let mut result: String = String::new();
+
+        while let Some(comment) = self.comments.get(self.next_comment.get()) {
+            if comment.get_line() >= line {
+                break;
+            }
+            result.push_str(&self.separator(comment.get_line()));
+            result.push_str(&self.indentation());
+            result.push_str(comment.get_text());
+            result.push('\n');
+            self.last_line.set(comment.get_end_line());
+            self.next_comment.set(self.next_comment.get() + 1);
+        }
+
+        return result;
+    }
+
+    // A blank line if the source had at least one before `line`.
+    fn separator(&self, line: u32) -> String {
+        let last_line: u32 = self.last_line.get();
+
+        if last_line > 0 && line > last_line + 1 {
+            return String::from("\n");
+        }
+        return String::new();
+    }
+
+    fn indentation(&self) -> String {
+        return INDENT.repeat(self.depth.get());
+    }
+
+    // Formats `expr` so that it still parses as the same tree when it appears where an
+    // expression of at least `min_precedence` is expected, adding parentheses only when needed.
+    fn expression(&self, expr: &Expr, min_precedence: u8) -> String {
+        let (text, precedence): (String, u8) = match expr {
+            Expr::Grouping(grouping) => {
+                return self.expression(&grouping.expression, min_precedence);
+            }
+            Expr::Literal(literal) => (Self::literal(literal), PRIMARY),
+            Expr::Variable(variable) => (variable.name.get_lexeme(), PRIMARY),
+            Expr::Assign(assign) => (
+                // Assignment is right-associative.
+                format!(
+                    "{} {} {}",
+                    assign.name.get_lexeme(),
+                    assign.operator.get_lexeme(),
+                    self.expression(&assign.value, ASSIGNMENT)
+                ),
+                ASSIGNMENT,
+            ),
+            Expr::Unary(unary) => {
+                let operator: String = unary.operator.get_lexeme();
+                let right: String = self.expression(&unary.right, UNARY);
+
+                // `- -x` rather than `--x`.
+                let separator: &str = match operator == "-" && right.starts_with('-') {
+                    true => " ",
+                    false => "",
+                };
+                (format!("{}{}{}", operator, separator, right), UNARY)
+            }
+            Expr::Conditional(conditional) => (
+                // The then branch sits between "?" and ":", so it never needs parentheses.
+                format!(
+                    "{} ? {} : {}",
+                    self.expression(&conditional.condition, OR),
+                    self.expression(&conditional.then_branch, COMMA),
+                    self.expression(&conditional.else_branch, ASSIGNMENT)
+                ),
+                CONDITIONAL,
+            ),
+            Expr::Comma(comma) => (
+                format!(
+                    "{}, {}",
+                    self.expression(&comma.left, COMMA),
+                    self.expression(&comma.right, ASSIGNMENT)
+                ),
+                COMMA,
+            ),
+            Expr::Logical(logical) => {
+                let precedence: u8 = match logical.operator.get_token_type() {
+                    TokenType::Or => OR,
+                    _ => AND,
+                };
+
+                (
+                    format!(
+                        "{} {} {}",
+                        self.expression(&logical.left, precedence),
+                        logical.operator.get_lexeme(),
+                        self.expression(&logical.right, precedence + 1)
+                    ),
+                    precedence,
+                )
+            }
+            Expr::Call(call) => {
+                let arguments: Vec<String> = call
+                    .arguments
+                    .iter()
+                    .map(|argument| self.expression(argument, ASSIGNMENT))
+                    .collect();
+
+                (
+                    format!(
+                        "{}({})",
+                        self.expression(&call.callee, CALL),
+                        arguments.join(", ")
+                    ),
+                    CALL,
+                )
+            }
+            Expr::List(list) => (
+                format!(
+                    "[{}]",
+                    list.elements
+                        .iter()
+                        .map(|element| self.expression(element, ASSIGNMENT))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+                PRIMARY,
+            ),
+            Expr::Map(map) => (
+                format!(
+                    "{{{}}}",
+                    map.entries
+                        .iter()
+                        .map(|(key, value)| format!(
+                            "{}: {}",
+                            self.expression(key, ASSIGNMENT),
+                            self.expression(value, ASSIGNMENT)
+                        ))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+                PRIMARY,
+            ),
+            // Neither bound can be a range itself.
+            Expr::Range(range) => (
+                format!(
+                    "{}{}{}",
+                    self.expression(&range.start, BIT_OR),
+                    range.operator.get_lexeme(),
+                    self.expression(&range.end, BIT_OR)
+                ),
+                RANGE,
+            ),
+            Expr::Get(get) => (
+                format!(
+                    "{}.{}",
+                    self.expression(&get.object, CALL),
+                    get.name.get_lexeme()
+                ),
+                CALL,
+            ),
+            Expr::Index(index) => (
+                format!(
+                    "{}[{}]",
+                    self.expression(&index.object, CALL),
+                    self.expression(&index.index, COMMA)
+                ),
+                CALL,
+            ),
+            Expr::SetIndex(set_index) => (
+                format!(
+                    "{}[{}] {} {}",
+                    self.expression(&set_index.object, CALL),
+                    self.expression(&set_index.index, COMMA),
+                    set_index.operator.get_lexeme(),
+                    self.expression(&set_index.value, ASSIGNMENT)
+                ),
+                ASSIGNMENT,
+            ),
+            // The string parts carry their "${" and "}".
+            Expr::Interpolation(interpolation) => (
+                interpolation
+                    .parts
+                    .iter()
+                    .map(|part| self.expression(part, ASSIGNMENT))
+                    .collect::<Vec<String>>()
+                    .concat(),
+                PRIMARY,
+            ),
+            Expr::Binary(binary) => {
+                let precedence: u8 = Self::binary_precedence(binary.operator.get_token_type());
+
+                // Every binary operator but "**" is left-associative, so an operand of the same
+                // precedence only needs parentheses on the right. The exponent of "**" is parsed
+                // as a unary expression, its base as a call.
+                let (left, right): (u8, u8) = match precedence {
+                    POWER => (CALL, UNARY),
+                    _ => (precedence, precedence + 1),
+                };
+                (
+                    format!(
+                        "{} {} {}",
+                        self.expression(&binary.left, left),
+                        binary.operator.get_lexeme(),
+                        self.expression(&binary.right, right)
+                    ),
+                    precedence,
+                )
+            }
+        };
+
+        if precedence < min_precedence {
+            return format!("({})", text);
+        }
+        return text;
+    }
+
+    // Statements between braces, one level deeper. `end` is the line of the closing brace.
+    fn block(&self, statements: &Vec<Stmt>, end: u32) -> String {
+        self.depth.set(self.depth.get() + 1);
+        self.last_line.set(0);
+        let block_end: u32 = self.block_end.replace(end);
+
+        let mut body: String = self.statements(statements);
+        body.push_str(&self.comments_before(end));
+
+        self.block_end.set(block_end);
+        self.depth.set(self.depth.get() - 1);
+        return match body.is_empty() {
+            true => String::from("{}"),
+            false => format!("{{\n{}{}}}", body, self.indentation()),
+        };
+    }
+
+    // The body of an if, else, while or for: a block stays on the same line, any other statement
+    // goes on the next one, indented.
+    fn body(&self, statement: &Stmt) -> String {
+        if let Stmt::Block(_) = statement {
+            return format!(" {}", statement.accept::<String>(self));
+        }
+
+        self.depth.set(self.depth.get() + 1);
+        let text: String = format!(
+            "\n{}{}",
+            self.indentation(),
+            statement.accept::<String>(self)
+        );
+        self.depth.set(self.depth.get() - 1);
+        return text;
+    }
+
+    // "label: " before a labeled loop.
+    fn label(label: &Option<Token>) -> String {
+        return match label {
+            Some(label) => format!("{}: ", label.get_lexeme()),
+            None => String::new(),
+        };
+    }
+
+    fn binary_precedence(token_type: &TokenType) -> u8 {
+        return match token_type {
+            TokenType::BangEqual | TokenType::EqualEqual => EQUALITY,
+            TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::Less
+            | TokenType::LessEqual => COMPARISON,
+            TokenType::Pipe => BIT_OR,
+            TokenType::Caret => BIT_XOR,
+            TokenType::Ampersand => BIT_AND,
+            TokenType::LessLess | TokenType::GreaterGreater => SHIFT,
+            TokenType::Plus | TokenType::Minus => TERM,
+            TokenType::StarStar => POWER,
+            _ => FACTOR,
+        };
+    }
+
+    fn literal(literal: &Literal) -> String {
+        return match literal {
+            Literal::Nil => String::from("nil"),
+            Literal::True => String::from("true"),
+            Literal::False => String::from("false"),
+            Literal::String { lexeme, .. } => lexeme.clone(),
+            Literal::Number { lexeme, .. } | Literal::Integer { lexeme, .. } => lexeme.clone(),
+        };
+    }
+}
+
+impl<'a> Visitor<Expr, String> for Formatter<'a> {
+    fn visit(&self, expr: &Expr) -> String {
+        return self.expression(expr, 0);
+    }
+}
+
+impl<'a> Visitor<Stmt, String> for Formatter<'a> {
+    fn visit(&self, statement: &Stmt) -> String {
+        return match statement {
+            Stmt::Expression(expression) => {
+                let text: String = expression.expression.accept::<String>(self);
+                // Where a statement starts, "{" opens a block unless a simple key and ":"
+                // follow, so a map there keeps parentheses.
+                match text.starts_with('{') {
+                    true => format!("({});", text),
+                    false => format!("{};", text),
+                }
+            }
+            Stmt::Print(print) => format!("print {};", print.expression.accept::<String>(self)),
+            Stmt::Var(var) => match &var.initializer {
+                Some(initializer) => format!(
+                    "var {} = {};",
+                    var.name.get_lexeme(),
+                    initializer.accept::<String>(self)
+                ),
+                None => format!("var {};", var.name.get_lexeme()),
+            },
+            Stmt::Block(block) => self.block(&block.statements, block.lines.end),
+            Stmt::If(if_stmt) => {
+                let mut text: String = format!(
+                    "if ({}){}",
+                    if_stmt.condition.accept::<String>(self),
+                    self.body(&if_stmt.then_branch)
+                );
+
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    match *if_stmt.then_branch {
+                        Stmt::Block(_) => text.push(' '),
+                        _ => text.push_str(&format!("\n{}", self.indentation())),
+                    }
+                    // `else if` chains stay flat.
+                    match **else_branch {
+                        Stmt::If(_) => {
+                            text.push_str(&format!("else {}", else_branch.accept::<String>(self)))
+                        }
+                        _ => text.push_str(&format!("else{}", self.body(else_branch))),
+                    }
+                }
+                text
+            }
+            Stmt::While(while_stmt) => format!(
+                "{}while ({}){}",
+                Self::label(&while_stmt.label),
+                while_stmt.condition.accept::<String>(self),
+                self.body(&while_stmt.body)
+            ),
+            Stmt::For(for_stmt) => {
+                let initializer: String = match &for_stmt.initializer {
+                    Some(initializer) => initializer.accept::<String>(self),
+                    None => String::from(";"),
+                };
+                let condition: String = match &for_stmt.condition {
+                    Some(condition) => format!(" {};", condition.accept::<String>(self)),
+                    None => String::from(";"),
+                };
+                let increment: String = match &for_stmt.increment {
+                    Some(increment) => format!(" {}", increment.accept::<String>(self)),
+                    None => String::new(),
+                };
+
+                format!(
+                    "{}for ({}{}{}){}",
+                    Self::label(&for_stmt.label),
+                    initializer,
+                    condition,
+                    increment,
+                    self.body(&for_stmt.body)
+                )
+            }
+            Stmt::ForIn(for_in) => format!(
+                "{}for ({} in {}){}",
+                Self::label(&for_in.label),
+                for_in.name.get_lexeme(),
+                for_in.iterable.accept::<String>(self),
+                self.body(&for_in.body)
+            ),
+            Stmt::Function(function) => {
+                let params: Vec<String> = function
+                    .params
+                    .iter()
+                    .map(|param| param.get_lexeme())
+                    .collect();
+
+                format!(
+                    "fun {}({}) {}",
+                    function.name.get_lexeme(),
+                    params.join(", "),
+                    self.block(&function.body, function.lines.end)
+                )
+            }
+            Stmt::Return(return_stmt) => match &return_stmt.value {
+                Some(value) => format!("return {};", value.accept::<String>(self)),
+                None => String::from("return;"),
+            },
+            Stmt::Import(import) => match &import.alias {
+                Some(alias) => format!(
+                    "import {} as {};",
+                    import.path.get_lexeme(),
+                    alias.get_lexeme()
+                ),
+                None => {
+                    let names: Vec<String> =
+                        import.names.iter().map(|name| name.get_lexeme()).collect();
+                    format!(
+                        "import {{ {} }} from {};",
+                        names.join(", "),
+                        import.path.get_lexeme()
+                    )
+                }
+            },
+            Stmt::Throw(throw) => format!("throw {};", throw.value.accept::<String>(self)),
+            Stmt::Try(try_stmt) => {
+                let mut text: String = format!(
+                    "try {}",
+                    self.block(&try_stmt.body.statements, try_stmt.body.lines.end)
+                );
+                if let Some((name, catch)) = &try_stmt.catch {
+                    text.push_str(&format!(
+                        " catch ({}) {}",
+                        name.get_lexeme(),
+                        self.block(&catch.statements, catch.lines.end)
+                    ));
+                }
+                if let Some(finally) = &try_stmt.finally {
+                    text.push_str(&format!(
+                        " finally {}",
+                        self.block(&finally.statements, finally.lines.end)
+                    ));
+                }
+                text
+            }
+            Stmt::Break(break_stmt) => match &break_stmt.label {
+                Some(label) => format!("break {};", label.get_lexeme()),
+                None => String::from("break;"),
+            },
+            Stmt::Continue(continue_stmt) => match &continue_stmt.label {
+                Some(label) => format!("continue {};", label.get_lexeme()),
+                None => String::from("continue;"),
+            },
+            Stmt::Test(test) => format!(
+                "test {} {}",
+                test.name.get_lexeme(),
+                self.block(&test.body, test.lines.end)
+            ),
+        };
+    }
+}
@@ -1,7 +1,11 @@
 pub mod ast;
 pub mod ast_printer;
+pub mod cst;
+pub mod formatter;
 pub mod parser;
 
 pub use ast::{Expr, Stmt, Visitor};
 pub use ast_printer::AstPrinter;
+pub use cst::{CstBuilder, SyntaxElement, SyntaxKind, SyntaxNode};
+pub use formatter::Formatter;
 pub use parser::Parser;
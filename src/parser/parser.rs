@@ -1,12 +1,27 @@
 use super::{ast, ast::Expr, ast::Stmt};
 use crate::lexer::{Token, TokenType};
-use crate::utils;
+use crate::utils::{self, SyntaxError};
+
+// Deepest nesting of statements and expressions. Everything that walks the tree recurses, so
+// without a limit a deep enough program overflows the stack.
+const MAX_DEPTH: usize = 256;
+
+// One of the parsing methods, e.g. Parser::unary.
+type Parse<'a, T> = fn(&mut Parser<'a>) -> Result<T, ParseError>;
 
 pub struct Parser<'a> {
     tokens: Vec<Token>,
     current: usize,
     had_err: &'a mut bool,
+    errors: Vec<SyntaxError>,
+    // how many function bodies enclose the current token.
+    function_depth: usize,
+    // labels of the loops enclosing the current token in the current function, innermost last.
+    loops: Vec<Option<Token>>,
+    // nesting of the statement or expression being parsed, see nest.
+    depth: usize,
 }
+#[allow(dead_code)]
 enum ParseError {
     Bad { msg: String, token: Token },
 }
@@ -17,6 +32,10 @@ impl<'a> Parser<'a> {
             tokens,
             current: 0,
             had_err,
+            errors: Vec::new(),
+            function_depth: 0,
+            loops: Vec::new(),
+            depth: 0,
         };
     }
 
@@ -24,27 +43,610 @@ impl<'a> Parser<'a> {
         let mut statements: Vec<Stmt> = Vec::new();
 
         while !self.is_at_end() {
-            match self.statement() {
+            match self.declaration() {
                 Ok(stmt) => statements.push(stmt),
-                Err(_) => (),
+                Err(_) => {
+                    self.depth = 0;
+                    self.synchronize();
+                }
             }
         }
         return statements;
     }
 
+    // A single expression filling the whole input, as typed at the debugger prompt.
+    pub fn parse_expression(&mut self) -> Option<Expr> {
+        let expr: Expr = self.expression().ok()?;
+
+        if !self.is_at_end() {
+            let _ = self.error::<()>(
+                self.peek().clone(),
+                String::from("Expect end of expression."),
+            );
+            return None;
+        }
+        return Some(expr);
+    }
+
+    pub fn get_errors(&self) -> &Vec<SyntaxError> {
+        return &self.errors;
+    }
+
+    // declaration -> fun_decl | import_decl | test_decl | var_decl | statement ;
+    fn declaration(&mut self) -> Result<Stmt, ParseError> {
+        let match_fun: Vec<TokenType> = vec![TokenType::Fun];
+        let match_import: Vec<TokenType> = vec![TokenType::Import];
+        let match_var: Vec<TokenType> = vec![TokenType::Var];
+
+        if self.match_token_type(&match_fun) {
+            return self.function();
+        }
+        if self.match_token_type(&match_import) {
+            return self.import_declaration();
+        }
+        if self.match_token_type(&match_var) {
+            return self.var_declaration();
+        }
+        if self.check_test() {
+            self.advance();
+            return self.test();
+        }
+        return self.statement();
+    }
+
+    // `test` is not a keyword: it only starts a test when a string follows, so variables and
+    // functions can still be named test.
+    fn check_test(&self) -> bool {
+        let is_test: bool =
+            *self.peek().get_token_type() == TokenType::Identifier(String::from("test"));
+        return match self.tokens.get(self.current + 1) {
+            Some(next) => is_test && matches!(next.get_token_type(), TokenType::String(_)),
+            None => false,
+        };
+    }
+
+    // A statement starting with "{" is a block, unless a key made of a single token and a ":"
+    // follow it: no statement but a labeled loop starts that way, so it is a map in an
+    // expression statement.
+    fn check_map(&self) -> bool {
+        if *self.peek().get_token_type() != TokenType::LeftBrace {
+            return false;
+        }
+        let is_key: bool = match self.tokens.get(self.current + 1) {
+            Some(key) => matches!(
+                key.get_token_type(),
+                TokenType::String(_)
+                    | TokenType::Number(_)
+                    | TokenType::Integer(_)
+                    | TokenType::Identifier(_)
+                    | TokenType::True
+                    | TokenType::False
+                    | TokenType::Nil
+            ),
+            None => false,
+        };
+        let is_loop: bool = match self.tokens.get(self.current + 3) {
+            Some(token) => matches!(token.get_token_type(), TokenType::For | TokenType::While),
+            None => false,
+        };
+        return match self.tokens.get(self.current + 2) {
+            Some(colon) => is_key && *colon.get_token_type() == TokenType::Colon && !is_loop,
+            None => false,
+        };
+    }
+
+    // test_decl -> "test" STRING block ;
+    fn test(&mut self) -> Result<Stmt, ParseError> {
+        let start: u32 = self.previous().get_line();
+        let name: Token = self.advance().clone();
+        self.consume(
+            TokenType::LeftBrace,
+            String::from("Expect '{' before test body."),
+        )?;
+
+        // A test runs like the body of a function without parameters.
+        self.function_depth += 1;
+        let loops: Vec<Option<Token>> = std::mem::take(&mut self.loops);
+        let body: Result<Vec<Stmt>, ParseError> = self.block_statements();
+        self.loops = loops;
+        self.function_depth -= 1;
+
+        return Ok(Stmt::Test(ast::Test {
+            name,
+            body: body?,
+            lines: self.lines_since(start),
+        }));
+    }
+
+    // fun_decl -> "fun" IDENTIFIER "(" parameters? ")" block ;
+    // parameters -> IDENTIFIER ( "," IDENTIFIER )* ;
+    fn function(&mut self) -> Result<Stmt, ParseError> {
+        let start: u32 = self.previous().get_line();
+        let name: Token = self.consume_identifier(String::from("Expect function name."))?;
+        self.consume(
+            TokenType::LeftParen,
+            String::from("Expect '(' after function name."),
+        )?;
+
+        let mut params: Vec<Token> = Vec::new();
+        if !self.check_token_type(&TokenType::RightParen) {
+            loop {
+                if params.len() >= 255 {
+                    let _ = self.error::<()>(
+                        self.peek().clone(),
+                        String::from("Can't have more than 255 parameters."),
+                    );
+                }
+                params.push(self.consume_identifier(String::from("Expect parameter name."))?);
+
+                if !self.match_token_type(&vec![TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(
+            TokenType::RightParen,
+            String::from("Expect ')' after parameters."),
+        )?;
+        self.consume(
+            TokenType::LeftBrace,
+            String::from("Expect '{' before function body."),
+        )?;
+
+        // break and continue cannot reach the loops around a function.
+        self.function_depth += 1;
+        let loops: Vec<Option<Token>> = std::mem::take(&mut self.loops);
+        let body: Result<Vec<Stmt>, ParseError> = self.block_statements();
+        self.loops = loops;
+        self.function_depth -= 1;
+
+        return Ok(Stmt::Function(ast::Function {
+            name,
+            params,
+            body: body?,
+            lines: self.lines_since(start),
+        }));
+    }
+
+    // import_decl -> "import" STRING "as" IDENTIFIER ";"
+    //              | "import" "{" IDENTIFIER ( "," IDENTIFIER )* "}" "from" STRING ";" ;
+    // Like "test", "as" and "from" are not keywords.
+    fn import_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let keyword: Token = self.previous().clone();
+        let mut alias: Option<Token> = None;
+        let mut names: Vec<Token> = Vec::new();
 
-    // statement -> expr_stmt | print_stmt ;
+        let path: Token = if self.match_token_type(&vec![TokenType::LeftBrace]) {
+            loop {
+                names.push(self.consume_identifier(String::from("Expect name to import."))?);
+                if !self.match_token_type(&vec![TokenType::Comma]) {
+                    break;
+                }
+            }
+            self.consume(
+                TokenType::RightBrace,
+                String::from("Expect '}' after imported names."),
+            )?;
+            self.consume_word("from", String::from("Expect 'from' after imported names."))?;
+            self.consume_path(String::from("Expect module path after 'from'."))?
+        } else {
+            let path: Token =
+                self.consume_path(String::from("Expect module path after 'import'."))?;
+            self.consume_word("as", String::from("Expect 'as' after module path."))?;
+            alias = Some(self.consume_identifier(String::from("Expect module name after 'as'."))?);
+            path
+        };
+
+        self.consume(
+            TokenType::Semicolon,
+            String::from("Expect ';' after import."),
+        )?;
+        return Ok(Stmt::Import(ast::Import {
+            lines: self.lines_since(keyword.get_line()),
+            keyword,
+            path,
+            alias,
+            names,
+        }));
+    }
+
+    // A plain string: the path of a module cannot be interpolated.
+    fn consume_path(&mut self, message: String) -> Result<Token, ParseError> {
+        if let TokenType::String(_) = self.peek().get_token_type() {
+            return Ok(self.advance().clone());
+        }
+        return self.error(self.peek().clone(), message);
+    }
+
+    fn consume_word(&mut self, word: &str, message: String) -> Result<Token, ParseError> {
+        if *self.peek().get_token_type() == TokenType::Identifier(String::from(word)) {
+            return Ok(self.advance().clone());
+        }
+        return self.error(self.peek().clone(), message);
+    }
+
+    // var_decl -> "var" IDENTIFIER ( "=" expression )? ";" ;
+    fn var_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start: u32 = self.previous().get_line();
+        let name: Token = self.consume_identifier(String::from("Expect variable name."))?;
+
+        let mut initializer: Option<Box<Expr>> = None;
+        if self.match_token_type(&vec![TokenType::Equal]) {
+            initializer = Some(Box::new(self.expression()?));
+        }
+
+        self.consume(
+            TokenType::Semicolon,
+            String::from("Expect ';' after variable declaration."),
+        )?;
+        return Ok(Stmt::Var(ast::Var {
+            name,
+            initializer,
+            lines: self.lines_since(start),
+        }));
+    }
+
+    // statement -> expr_stmt | for_stmt | if_stmt | print_stmt | return_stmt | while_stmt
+    //            | break_stmt | continue_stmt | labeled_stmt | throw_stmt | try_stmt | block ;
     fn statement(&mut self) -> Result<Stmt, ParseError> {
+        let match_break: Vec<TokenType> = vec![TokenType::Break];
+        let match_continue: Vec<TokenType> = vec![TokenType::Continue];
+        let match_for: Vec<TokenType> = vec![TokenType::For];
+        let match_if: Vec<TokenType> = vec![TokenType::If];
         let match_print: Vec<TokenType> = vec![TokenType::Print];
+        let match_return: Vec<TokenType> = vec![TokenType::Return];
+        let match_throw: Vec<TokenType> = vec![TokenType::Throw];
+        let match_try: Vec<TokenType> = vec![TokenType::Try];
+        let match_while: Vec<TokenType> = vec![TokenType::While];
+        let match_block: Vec<TokenType> = vec![TokenType::LeftBrace];
 
+        if self.match_token_type(&match_break) {
+            return self.break_statement();
+        }
+        if self.match_token_type(&match_continue) {
+            return self.continue_statement();
+        }
+        if self.check_label() {
+            return self.labeled_statement();
+        }
+        if self.match_token_type(&match_for) {
+            return self.for_statement(None);
+        }
+        if self.match_token_type(&match_if) {
+            return self.if_statement();
+        }
         if self.match_token_type(&match_print) {
             return self.print_statement();
         }
+        if self.match_token_type(&match_return) {
+            return self.return_statement();
+        }
+        if self.match_token_type(&match_while) {
+            return self.while_statement(None);
+        }
+        if self.match_token_type(&match_throw) {
+            return self.throw_statement();
+        }
+        if self.match_token_type(&match_try) {
+            return self.try_statement();
+        }
+        if !self.check_map() && self.match_token_type(&match_block) {
+            return self.block();
+        }
         return self.expression_statement();
     }
 
+    // No expression starts with an identifier followed by a ":".
+    fn check_label(&self) -> bool {
+        let is_name: bool = matches!(self.peek().get_token_type(), TokenType::Identifier(_));
+        return match self.tokens.get(self.current + 1) {
+            Some(next) => is_name && *next.get_token_type() == TokenType::Colon,
+            None => false,
+        };
+    }
+
+    // labeled_stmt -> IDENTIFIER ":" ( for_stmt | while_stmt ) ;
+    fn labeled_statement(&mut self) -> Result<Stmt, ParseError> {
+        let label: Token = self.advance().clone();
+        // Consuming the ":".
+        self.advance();
+
+        if self.match_token_type(&vec![TokenType::For]) {
+            return self.for_statement(Some(label));
+        }
+        if self.match_token_type(&vec![TokenType::While]) {
+            return self.while_statement(Some(label));
+        }
+        return self.error(
+            self.peek().clone(),
+            String::from("Expect a loop after a label."),
+        );
+    }
+
+    // break_stmt -> "break" IDENTIFIER? ";" ;
+    fn break_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword: Token = self.previous().clone();
+        let label: Option<Token> = self.jump_label(&keyword);
+        let _ = self.consume(
+            TokenType::Semicolon,
+            String::from("Expect ';' after 'break'."),
+        );
+        return Ok(Stmt::Break(ast::Break {
+            lines: self.lines_since(keyword.get_line()),
+            keyword,
+            label,
+        }));
+    }
+
+    // continue_stmt -> "continue" IDENTIFIER? ";" ;
+    fn continue_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword: Token = self.previous().clone();
+        let label: Option<Token> = self.jump_label(&keyword);
+        let _ = self.consume(
+            TokenType::Semicolon,
+            String::from("Expect ';' after 'continue'."),
+        );
+        return Ok(Stmt::Continue(ast::Continue {
+            lines: self.lines_since(keyword.get_line()),
+            keyword,
+            label,
+        }));
+    }
+
+    // The label after a break or continue, reporting a jump that no enclosing loop can take.
+    fn jump_label(&mut self, keyword: &Token) -> Option<Token> {
+        let mut label: Option<Token> = None;
+        if let TokenType::Identifier(_) = self.peek().get_token_type() {
+            label = Some(self.advance().clone());
+        }
+
+        if self.loops.is_empty() {
+            let _ = self.error::<()>(
+                keyword.clone(),
+                format!("Can't use '{}' outside of a loop.", keyword.get_lexeme()),
+            );
+        } else if let Some(name) = &label {
+            let is_enclosing: bool = self.loops.iter().any(|enclosing| match enclosing {
+                Some(enclosing) => enclosing.get_lexeme() == name.get_lexeme(),
+                None => false,
+            });
+            if !is_enclosing {
+                let _ = self.error::<()>(
+                    name.clone(),
+                    format!("No enclosing loop labeled '{}'.", name.get_lexeme()),
+                );
+            }
+        }
+        return label;
+    }
+
+    // The body of a loop, which break and continue can jump out of.
+    fn loop_body(&mut self, label: &Option<Token>) -> Result<Stmt, ParseError> {
+        self.loops.push(label.clone());
+        let body: Result<Stmt, ParseError> = self.nested(Self::statement);
+        self.loops.pop();
+        return body;
+    }
+
+    // for_stmt -> "for" "(" ( var_decl | expr_stmt | ";" ) expression? ";" expression? ")" statement
+    //           | "for" "(" IDENTIFIER "in" expression ")" statement ;
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Stmt, ParseError> {
+        let start: u32 = self.previous().get_line();
+        self.consume(
+            TokenType::LeftParen,
+            String::from("Expect '(' after 'for'."),
+        )?;
+        if self.check_for_in() {
+            return self.for_in_statement(label, start);
+        }
+
+        let initializer: Option<Box<Stmt>> = if self.match_token_type(&vec![TokenType::Semicolon]) {
+            None
+        } else if self.match_token_type(&vec![TokenType::Var]) {
+            Some(Box::new(self.var_declaration()?))
+        } else {
+            Some(Box::new(self.expression_statement()?))
+        };
+
+        let mut condition: Option<Box<Expr>> = None;
+        if !self.check_token_type(&TokenType::Semicolon) {
+            condition = Some(Box::new(self.expression()?));
+        }
+        self.consume(
+            TokenType::Semicolon,
+            String::from("Expect ';' after loop condition."),
+        )?;
+
+        let mut increment: Option<Box<Expr>> = None;
+        if !self.check_token_type(&TokenType::RightParen) {
+            increment = Some(Box::new(self.expression()?));
+        }
+        self.consume(
+            TokenType::RightParen,
+            String::from("Expect ')' after for clauses."),
+        )?;
+
+        let body: Stmt = self.loop_body(&label)?;
+        return Ok(Stmt::For(ast::For {
+            label,
+            initializer,
+            condition,
+            increment,
+            body: Box::new(body),
+            lines: self.lines_since(start),
+        }));
+    }
+
+    fn check_for_in(&self) -> bool {
+        let is_name: bool = matches!(self.peek().get_token_type(), TokenType::Identifier(_));
+        return match self.tokens.get(self.current + 1) {
+            Some(next) => is_name && *next.get_token_type() == TokenType::In,
+            None => false,
+        };
+    }
+
+    // After "for" "(", with `start` the line of the "for".
+    fn for_in_statement(&mut self, label: Option<Token>, start: u32) -> Result<Stmt, ParseError> {
+        let name: Token = self.advance().clone();
+        // Consuming the "in".
+        self.advance();
+        let iterable: Expr = self.expression()?;
+        self.consume(
+            TokenType::RightParen,
+            String::from("Expect ')' after for clauses."),
+        )?;
+
+        let body: Stmt = self.loop_body(&label)?;
+        return Ok(Stmt::ForIn(ast::ForIn {
+            label,
+            name,
+            iterable: Box::new(iterable),
+            body: Box::new(body),
+            lines: self.lines_since(start),
+        }));
+    }
+
+    // if_stmt -> "if" "(" expression ")" statement ( "else" statement )? ;
+    fn if_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start: u32 = self.previous().get_line();
+        self.consume(TokenType::LeftParen, String::from("Expect '(' after 'if'."))?;
+        let condition: Expr = self.expression()?;
+        self.consume(
+            TokenType::RightParen,
+            String::from("Expect ')' after if condition."),
+        )?;
+
+        let then_branch: Stmt = self.nested(Self::statement)?;
+        let mut else_branch: Option<Box<Stmt>> = None;
+        if self.match_token_type(&vec![TokenType::Else]) {
+            else_branch = Some(Box::new(self.nested(Self::statement)?));
+        }
+
+        return Ok(Stmt::If(ast::If {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch,
+            lines: self.lines_since(start),
+        }));
+    }
+
+    // return_stmt -> "return" expression? ";" ;
+    fn return_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword: Token = self.previous().clone();
+        if self.function_depth == 0 {
+            let _ = self.error::<()>(
+                keyword.clone(),
+                String::from("Can't return from top-level code."),
+            );
+        }
+
+        let mut value: Option<Box<Expr>> = None;
+        if !self.check_token_type(&TokenType::Semicolon) {
+            value = Some(Box::new(self.expression()?));
+        }
+        self.consume(
+            TokenType::Semicolon,
+            String::from("Expect ';' after return value."),
+        )?;
+
+        return Ok(Stmt::Return(ast::Return {
+            lines: self.lines_since(keyword.get_line()),
+            keyword,
+            value,
+        }));
+    }
+
+    // while_stmt -> "while" "(" expression ")" statement ;
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Stmt, ParseError> {
+        let start: u32 = self.previous().get_line();
+        self.consume(
+            TokenType::LeftParen,
+            String::from("Expect '(' after 'while'."),
+        )?;
+        let condition: Expr = self.expression()?;
+        self.consume(
+            TokenType::RightParen,
+            String::from("Expect ')' after condition."),
+        )?;
+        let body: Stmt = self.loop_body(&label)?;
+
+        return Ok(Stmt::While(ast::While {
+            label,
+            condition: Box::new(condition),
+            body: Box::new(body),
+            lines: self.lines_since(start),
+        }));
+    }
+
+    // throw_stmt -> "throw" expression ";" ;
+    fn throw_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword: Token = self.previous().clone();
+        let value: Expr = self.expression()?;
+        let _ = self.consume(
+            TokenType::Semicolon,
+            String::from("Expect ';' after thrown value."),
+        );
+        return Ok(Stmt::Throw(ast::Throw {
+            lines: self.lines_since(keyword.get_line()),
+            keyword,
+            value: Box::new(value),
+        }));
+    }
+
+    // try_stmt -> "try" block ( "catch" "(" IDENTIFIER ")" block )? ( "finally" block )? ;
+    fn try_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword: Token = self.previous().clone();
+        let body: ast::Block = self.clause_block("try")?;
+
+        let mut catch: Option<(Token, ast::Block)> = None;
+        if self.match_token_type(&vec![TokenType::Catch]) {
+            self.consume(
+                TokenType::LeftParen,
+                String::from("Expect '(' after 'catch'."),
+            )?;
+            let name: Token = self.consume_identifier(String::from("Expect error name."))?;
+            self.consume(
+                TokenType::RightParen,
+                String::from("Expect ')' after error name."),
+            )?;
+            catch = Some((name, self.clause_block("catch")?));
+        }
+        let mut finally: Option<ast::Block> = None;
+        if self.match_token_type(&vec![TokenType::Finally]) {
+            finally = Some(self.clause_block("finally")?);
+        }
+
+        if catch.is_none() && finally.is_none() {
+            return self.error(
+                self.peek().clone(),
+                String::from("Expect 'catch' or 'finally' after try block."),
+            );
+        }
+        return Ok(Stmt::Try(ast::Try {
+            body,
+            catch,
+            finally,
+            lines: self.lines_since(keyword.get_line()),
+        }));
+    }
+
+    // The block after "try", "catch" or "finally".
+    fn clause_block(&mut self, clause: &str) -> Result<ast::Block, ParseError> {
+        self.consume(
+            TokenType::LeftBrace,
+            format!("Expect '{{' before {} body.", clause),
+        )?;
+        let start: u32 = self.previous().get_line();
+        let statements: Vec<Stmt> = self.nested(Self::block_statements)?;
+        return Ok(ast::Block {
+            statements,
+            lines: self.lines_since(start),
+        });
+    }
+
     // print_stmt -> "print" expression ";" ;
     fn print_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start: u32 = self.previous().get_line();
         let value: Expr = self.expression()?;
         let _ = self.consume(
             TokenType::Semicolon,
@@ -52,11 +654,46 @@ impl<'a> Parser<'a> {
         );
         return Ok(Stmt::Print(ast::Print {
             expression: Box::new(value),
+            lines: self.lines_since(start),
         }));
     }
 
+    // block -> "{" declaration* "}" ;
+    fn block(&mut self) -> Result<Stmt, ParseError> {
+        let start: u32 = self.previous().get_line();
+        let statements: Vec<Stmt> = self.block_statements()?;
+
+        return Ok(Stmt::Block(ast::Block {
+            statements,
+            lines: self.lines_since(start),
+        }));
+    }
+
+    // The declarations of a block whose "{" has been consumed, and its "}".
+    fn block_statements(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let mut statements: Vec<Stmt> = Vec::new();
+
+        let depth: usize = self.depth;
+        while !self.check_token_type(&TokenType::RightBrace) && !self.is_at_end() {
+            match self.nested(Self::declaration) {
+                Ok(stmt) => statements.push(stmt),
+                Err(_) => {
+                    self.depth = depth;
+                    self.synchronize();
+                }
+            }
+        }
+
+        self.consume(
+            TokenType::RightBrace,
+            String::from("Expect '}' after block."),
+        )?;
+        return Ok(statements);
+    }
+
     // expr_stmt -> expression ";" ;
     fn expression_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start: u32 = self.peek().get_line();
         let expr: Expr = self.expression()?;
         let _ = self.consume(
             TokenType::Semicolon,
@@ -64,22 +701,148 @@ impl<'a> Parser<'a> {
         );
         return Ok(Stmt::Expression(ast::Expression {
             expression: Box::new(expr),
+            lines: self.lines_since(start),
         }));
     }
 
-    //expression -> equality ;
+    //expression -> comma ;
     fn expression(&mut self) -> Result<Expr, ParseError> {
-        return self.equality();
+        return self.nested(Self::comma);
+    }
+
+    //comma -> assignment ( "," assignment )* ;
+    fn comma(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = self.assignment()?;
+        let depth: usize = self.depth;
+
+        while self.match_token_type(&vec![TokenType::Comma]) {
+            self.nest()?;
+            let right: Expr = self.assignment()?;
+            expr = Expr::Comma(ast::Comma {
+                left: Box::new(expr),
+                right: Box::new(right),
+            });
+        }
+
+        self.depth = depth;
+        return Ok(expr);
+    }
+
+    //assignment -> ( IDENTIFIER | call "[" expression "]" ) ( "=" | "+=" | "-=" | "*=" | "/=" | "%=" ) assignment
+    //            | conditional ;
+    fn assignment(&mut self) -> Result<Expr, ParseError> {
+        let expr: Expr = self.conditional()?;
+
+        let types_to_match: Vec<TokenType> = vec![
+            TokenType::Equal,
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+            TokenType::PercentEqual,
+        ];
+
+        if self.match_token_type(&types_to_match) {
+            let operator: Token = self.previous().clone();
+            let value: Expr = self.nested(Self::assignment)?;
+
+            return match expr {
+                Expr::Variable(variable) => Ok(Expr::Assign(ast::Assign {
+                    name: variable.name,
+                    operator,
+                    value: Box::new(value),
+                })),
+                Expr::Index(index) => Ok(Expr::SetIndex(ast::SetIndex {
+                    object: index.object,
+                    bracket: index.bracket,
+                    index: index.index,
+                    operator,
+                    value: Box::new(value),
+                })),
+                expr => {
+                    // Reported, but the parser is not confused, so no need to synchronize.
+                    let _ = self.error::<()>(operator, String::from("Invalid assignment target."));
+                    Ok(expr)
+                }
+            };
+        }
+
+        return Ok(expr);
+    }
+
+    //conditional -> logic_or ( "?" expression ":" assignment )? ;
+    fn conditional(&mut self) -> Result<Expr, ParseError> {
+        let condition: Expr = self.or()?;
+
+        if self.match_token_type(&vec![TokenType::Question]) {
+            let then_branch: Expr = self.expression()?;
+            self.consume(
+                TokenType::Colon,
+                String::from("Expect ':' after then branch of conditional expression."),
+            )?;
+            // An assignment or another conditional, which makes "?:" right-associative.
+            let else_branch: Expr = self.nested(Self::assignment)?;
+
+            return Ok(Expr::Conditional(ast::Conditional {
+                condition: Box::new(condition),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            }));
+        }
+
+        return Ok(condition);
+    }
+
+    //logic_or -> logic_and ( "or" logic_and )* ;
+    fn or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.and()?;
+        let depth: usize = self.depth;
+
+        while self.match_token_type(&vec![TokenType::Or]) {
+            let operator: Token = self.previous().clone();
+            self.nest()?;
+            let right: Expr = self.and()?;
+            expr = Expr::Logical(ast::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        self.depth = depth;
+        return Ok(expr);
+    }
+
+    //logic_and -> equality ( "and" equality )* ;
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.equality()?;
+        let depth: usize = self.depth;
+
+        while self.match_token_type(&vec![TokenType::And]) {
+            let operator: Token = self.previous().clone();
+            self.nest()?;
+            let right: Expr = self.equality()?;
+            expr = Expr::Logical(ast::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        self.depth = depth;
+        return Ok(expr);
     }
 
     //equality -> comparison (("!=" | "==") comparison)* ;
     fn equality(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.comparison()?;
+        let depth: usize = self.depth;
 
         let types_to_match: Vec<TokenType> = vec![TokenType::BangEqual, TokenType::EqualEqual];
 
         while self.match_token_type(&types_to_match) {
             let operator: Token = self.previous().clone();
+            self.nest()?;
             let right: Expr = self.comparison()?;
             expr = Expr::Binary(ast::Binary {
                 left: Box::new(expr),
@@ -88,12 +851,14 @@ impl<'a> Parser<'a> {
             });
         }
 
+        self.depth = depth;
         return Ok(expr);
     }
 
-    //comparison -> term ((">" | ">=" | "<=") term)* ;
+    //comparison -> range ((">" | ">=" | "<=") range)* ;
     fn comparison(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = self.term()?;
+        let mut expr = self.range()?;
+        let depth: usize = self.depth;
 
         let types_to_match: Vec<TokenType> = vec![
             TokenType::Greater,
@@ -104,6 +869,112 @@ impl<'a> Parser<'a> {
 
         while self.match_token_type(&types_to_match) {
             let operator: Token = self.previous().clone();
+            self.nest()?;
+            let right: Expr = self.range()?;
+            expr = Expr::Binary(ast::Binary {
+                left: Box::new(expr),
+                operator: operator,
+                right: Box::new(right),
+            });
+        }
+
+        self.depth = depth;
+        return Ok(expr);
+    }
+
+    // Not associative: "0..1..2" is an error.
+    //range -> bit_or ( ( ".." | "..=" ) bit_or )? ;
+    fn range(&mut self) -> Result<Expr, ParseError> {
+        let expr: Expr = self.bit_or()?;
+
+        if self.match_token_type(&vec![TokenType::DotDot, TokenType::DotDotEqual]) {
+            let operator: Token = self.previous().clone();
+            let end: Expr = self.nested(Self::bit_or)?;
+            return Ok(Expr::Range(ast::Range {
+                start: Box::new(expr),
+                operator,
+                end: Box::new(end),
+            }));
+        }
+        return Ok(expr);
+    }
+
+    //bit_or -> bit_xor ( "|" bit_xor )* ;
+    fn bit_or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.bit_xor()?;
+        let depth: usize = self.depth;
+
+        let types_to_match: Vec<TokenType> = vec![TokenType::Pipe];
+
+        while self.match_token_type(&types_to_match) {
+            let operator: Token = self.previous().clone();
+            self.nest()?;
+            let right: Expr = self.bit_xor()?;
+            expr = Expr::Binary(ast::Binary {
+                left: Box::new(expr),
+                operator: operator,
+                right: Box::new(right),
+            });
+        }
+
+        self.depth = depth;
+        return Ok(expr);
+    }
+
+    //bit_xor -> bit_and ( "^" bit_and )* ;
+    fn bit_xor(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.bit_and()?;
+        let depth: usize = self.depth;
+
+        let types_to_match: Vec<TokenType> = vec![TokenType::Caret];
+
+        while self.match_token_type(&types_to_match) {
+            let operator: Token = self.previous().clone();
+            self.nest()?;
+            let right: Expr = self.bit_and()?;
+            expr = Expr::Binary(ast::Binary {
+                left: Box::new(expr),
+                operator: operator,
+                right: Box::new(right),
+            });
+        }
+
+        self.depth = depth;
+        return Ok(expr);
+    }
+
+    //bit_and -> shift ( "&" shift )* ;
+    fn bit_and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.shift()?;
+        let depth: usize = self.depth;
+
+        let types_to_match: Vec<TokenType> = vec![TokenType::Ampersand];
+
+        while self.match_token_type(&types_to_match) {
+            let operator: Token = self.previous().clone();
+            self.nest()?;
+            let right: Expr = self.shift()?;
+            expr = Expr::Binary(ast::Binary {
+                left: Box::new(expr),
+                operator: operator,
+                right: Box::new(right),
+            });
+        }
+
+        self.depth = depth;
+        return Ok(expr);
+    }
+
+    //shift -> term ( ( "<<" | ">>" ) term )* ;
+    fn shift(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.term()?;
+        let depth: usize = self.depth;
+
+        let types_to_match: Vec<TokenType> = vec![TokenType::LessLess, TokenType::GreaterGreater];
+
+        while self.match_token_type(&types_to_match) {
+            let operator: Token = self.previous().clone();
+            self.nest()?;
             let right: Expr = self.term()?;
             expr = Expr::Binary(ast::Binary {
                 left: Box::new(expr),
@@ -112,17 +983,20 @@ impl<'a> Parser<'a> {
             });
         }
 
+        self.depth = depth;
         return Ok(expr);
     }
 
     //term -> factor (("+" | "-") factor)* ;
     fn term(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.factor()?;
+        let depth: usize = self.depth;
 
         let types_to_match: Vec<TokenType> = vec![TokenType::Plus, TokenType::Minus];
 
         while self.match_token_type(&types_to_match) {
             let operator: Token = self.previous().clone();
+            self.nest()?;
             let right: Expr = self.factor()?;
             expr = Expr::Binary(ast::Binary {
                 left: Box::new(expr),
@@ -131,17 +1005,21 @@ impl<'a> Parser<'a> {
             });
         }
 
+        self.depth = depth;
         return Ok(expr);
     }
 
-    //factor -> unary (("*" | "/") unary)* ;
+    //factor -> unary (("*" | "/" | "%") unary)* ;
     fn factor(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.unary()?;
+        let depth: usize = self.depth;
 
-        let types_to_match: Vec<TokenType> = vec![TokenType::Star, TokenType::Slash];
+        let types_to_match: Vec<TokenType> =
+            vec![TokenType::Star, TokenType::Slash, TokenType::Percent];
 
         while self.match_token_type(&types_to_match) {
             let operator: Token = self.previous().clone();
+            self.nest()?;
             let right: Expr = self.unary()?;
             expr = Expr::Binary(ast::Binary {
                 left: Box::new(expr),
@@ -150,16 +1028,18 @@ impl<'a> Parser<'a> {
             });
         }
 
+        self.depth = depth;
         return Ok(expr);
     }
 
-    //unary -> ("!" | "-") unary | primary ;
+    //unary -> ("!" | "-" | "~") unary | power ;
     fn unary(&mut self) -> Result<Expr, ParseError> {
-        let types_to_match: Vec<TokenType> = vec![TokenType::Bang, TokenType::Minus];
+        let types_to_match: Vec<TokenType> =
+            vec![TokenType::Bang, TokenType::Minus, TokenType::Tilde];
 
         if self.match_token_type(&types_to_match) {
             let operator: Token = self.previous().clone();
-            let right: Expr = self.unary()?;
+            let right: Expr = self.nested(Self::unary)?;
             let expr = Expr::Unary(ast::Unary {
                 operator: operator,
                 right: Box::new(right),
@@ -167,32 +1047,276 @@ impl<'a> Parser<'a> {
             return Ok(expr);
         }
 
-        return self.primary();
+        return self.power();
+    }
+
+    //power -> call ( "**" unary )? ;
+    fn power(&mut self) -> Result<Expr, ParseError> {
+        let expr: Expr = self.call()?;
+
+        if self.match_token_type(&vec![TokenType::StarStar]) {
+            let operator: Token = self.previous().clone();
+            // The exponent may be a power itself, which makes "**" right-associative, and binds
+            // tighter than a unary operator on its left: -2 ** 2 is -(2 ** 2).
+            let right: Expr = self.nested(Self::unary)?;
+            return Ok(Expr::Binary(ast::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            }));
+        }
+
+        return Ok(expr);
+    }
+
+    //call -> primary ( "(" arguments? ")" | "[" expression "]" | "." IDENTIFIER )* ;
+    fn call(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = self.primary()?;
+        let depth: usize = self.depth;
+
+        loop {
+            if self.match_token_type(&vec![TokenType::LeftParen]) {
+                self.nest()?;
+                expr = self.finish_call(expr)?;
+            } else if self.match_token_type(&vec![TokenType::LeftBracket]) {
+                self.nest()?;
+                let index: Expr = self.expression()?;
+                let bracket: Token = self
+                    .consume(
+                        TokenType::RightBracket,
+                        String::from("Expect ']' after index."),
+                    )?
+                    .clone();
+                expr = Expr::Index(ast::Index {
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                });
+            } else if self.match_token_type(&vec![TokenType::Dot]) {
+                self.nest()?;
+                let name: Token =
+                    self.consume_identifier(String::from("Expect property name after '.'."))?;
+                expr = Expr::Get(ast::Get {
+                    object: Box::new(expr),
+                    name,
+                });
+            } else {
+                break;
+            }
+        }
+
+        self.depth = depth;
+        return Ok(expr);
+    }
+
+    //arguments -> assignment ( "," assignment )* ;
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, ParseError> {
+        let mut arguments: Vec<Expr> = Vec::new();
+
+        if !self.check_token_type(&TokenType::RightParen) {
+            loop {
+                if arguments.len() >= 255 {
+                    let _ = self.error::<()>(
+                        self.peek().clone(),
+                        String::from("Can't have more than 255 arguments."),
+                    );
+                }
+                // Not expression(), a "," separates arguments.
+                arguments.push(self.nested(Self::assignment)?);
+
+                if !self.match_token_type(&vec![TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        let paren: Token = self
+            .consume(
+                TokenType::RightParen,
+                String::from("Expect ')' after arguments."),
+            )?
+            .clone();
+
+        return Ok(Expr::Call(ast::Call {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+        }));
     }
 
-    //primary -> NUMBER | STRING | "true" | "false" | "nil" | "(" expression ")" ;
+    //primary -> NUMBER | STRING | interpolation | list | map | "true" | "false" | "nil"
+    //        | "(" expression ")" | IDENTIFIER ;
     fn primary(&mut self) -> Result<Expr, ParseError> {
-        let expr = match self.advance().get_token_type() {
+        let expr = match self.peek().get_token_type() {
+            TokenType::Interpolation(_) => return self.interpolation(),
+            TokenType::LeftBracket => return self.list(),
+            TokenType::LeftBrace => return self.map(),
+            TokenType::Identifier(_) => Expr::Variable(ast::Variable {
+                name: self.peek().clone(),
+            }),
             TokenType::False => Expr::Literal(ast::Literal::False),
             TokenType::True => Expr::Literal(ast::Literal::True),
             TokenType::Nil => Expr::Literal(ast::Literal::Nil),
-            TokenType::String(string) => Expr::Literal(ast::Literal::String(string.clone())),
-            TokenType::Number(number) => Expr::Literal(ast::Literal::Number(*number)),
+            TokenType::String(string) => Expr::Literal(ast::Literal::String {
+                value: string.clone(),
+                lexeme: self.peek().get_lexeme(),
+            }),
+            TokenType::Number(number) => Expr::Literal(ast::Literal::Number {
+                value: *number,
+                lexeme: self.peek().get_lexeme(),
+            }),
+            TokenType::Integer(integer) => Expr::Literal(ast::Literal::Integer {
+                value: *integer,
+                lexeme: self.peek().get_lexeme(),
+            }),
             TokenType::LeftParen => {
+                self.advance();
                 let expr: Expr = self.expression()?;
                 self.consume(
                     TokenType::RightParen,
                     String::from("Expect ')' after expression."),
                 )?;
-                Expr::Grouping(ast::Grouping {
+                return Ok(Expr::Grouping(ast::Grouping {
                     expression: Box::new(expr),
-                })
+                }));
             }
-            _ => self.error(self.peek().clone(), String::from("Expected expression!"))?,
+            token_type => match Self::right_operand(token_type) {
+                Some(operand) => return self.missing_left_operand(operand),
+                // Not consumed, so the error points at it and synchronize() starts from it.
+                None => {
+                    return self.error(self.peek().clone(), String::from("Expected expression!"))
+                }
+            },
         };
+        self.advance();
 
         return Ok(expr);
     }
+    // list -> "[" ( assignment ( "," assignment )* ","? )? "]" ;
+    fn list(&mut self) -> Result<Expr, ParseError> {
+        // Consuming the "[".
+        self.advance();
+        let mut elements: Vec<Expr> = Vec::new();
+
+        while !self.check_token_type(&TokenType::RightBracket) && !self.is_at_end() {
+            elements.push(self.nested(Self::assignment)?);
+            if !self.match_token_type(&vec![TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(
+            TokenType::RightBracket,
+            String::from("Expect ']' after list elements."),
+        )?;
+
+        return Ok(Expr::List(ast::List { elements }));
+    }
+
+    // map -> "{" ( entry ( "," entry )* ","? )? "}" ;
+    // entry -> assignment ":" assignment ;
+    fn map(&mut self) -> Result<Expr, ParseError> {
+        // Consuming the "{".
+        self.advance();
+        let mut entries: Vec<(Expr, Expr)> = Vec::new();
+
+        while !self.check_token_type(&TokenType::RightBrace) && !self.is_at_end() {
+            let key: Expr = self.nested(Self::assignment)?;
+            self.consume(TokenType::Colon, String::from("Expect ':' after map key."))?;
+            let value: Expr = self.nested(Self::assignment)?;
+            entries.push((key, value));
+            if !self.match_token_type(&vec![TokenType::Comma]) {
+                break;
+            }
+        }
+        let brace: Token = self
+            .consume(
+                TokenType::RightBrace,
+                String::from("Expect '}' after map entries."),
+            )?
+            .clone();
+
+        return Ok(Expr::Map(ast::Map { brace, entries }));
+    }
+
+    // Error production: a binary operator where an expression should start, as in "* 3". The
+    // right operand is parsed as if the left one were there and stands in for the whole
+    // expression, so parsing goes on without synchronizing.
+    fn missing_left_operand(&mut self, operand: Parse<'a, Expr>) -> Result<Expr, ParseError> {
+        let operator: Token = self.advance().clone();
+        let _ = self.error::<()>(
+            operator.clone(),
+            format!(
+                "Binary operator '{}' requires a left operand.",
+                operator.get_lexeme()
+            ),
+        );
+        return self.nested(operand);
+    }
+
+    // How the right operand of a binary operator is parsed, None for any other token. "-" is
+    // left out, it starts a unary expression.
+    fn right_operand(token_type: &TokenType) -> Option<Parse<'a, Expr>> {
+        let operand: Parse<'a, Expr> = match token_type {
+            TokenType::Or => Self::and,
+            TokenType::And => Self::equality,
+            TokenType::BangEqual | TokenType::EqualEqual => Self::comparison,
+            TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::Less
+            | TokenType::LessEqual => Self::range,
+            TokenType::DotDot | TokenType::DotDotEqual => Self::bit_or,
+            TokenType::Pipe => Self::bit_xor,
+            TokenType::Caret => Self::bit_and,
+            TokenType::Ampersand => Self::shift,
+            TokenType::LessLess | TokenType::GreaterGreater => Self::term,
+            TokenType::Plus => Self::factor,
+            TokenType::Star | TokenType::Slash | TokenType::Percent | TokenType::StarStar => {
+                Self::unary
+            }
+            _ => return None,
+        };
+        return Some(operand);
+    }
+
+    // interpolation -> INTERPOLATION expression ( INTERPOLATION expression )* STRING ;
+    fn interpolation(&mut self) -> Result<Expr, ParseError> {
+        let mut parts: Vec<Expr> = Vec::new();
+
+        loop {
+            let segment: Token = self.advance().clone();
+            let value: String = match segment.get_token_type() {
+                TokenType::Interpolation(value) | TokenType::String(value) => value.clone(),
+                _ => unreachable!("only string segments are consumed here"),
+            };
+            parts.push(Expr::Literal(ast::Literal::String {
+                value,
+                lexeme: segment.get_lexeme(),
+            }));
+            if let TokenType::String(_) = segment.get_token_type() {
+                break;
+            }
+
+            // The rest of the string would otherwise parse as the expression.
+            if self.peek().get_lexeme().starts_with('}') {
+                return self.error(
+                    self.peek().clone(),
+                    String::from("Expect expression inside '${}'."),
+                );
+            }
+            parts.push(self.expression()?);
+            if !matches!(
+                self.peek().get_token_type(),
+                TokenType::Interpolation(_) | TokenType::String(_)
+            ) {
+                return self.error(
+                    self.peek().clone(),
+                    String::from("Expect '}' after interpolated expression."),
+                );
+            }
+        }
+
+        return Ok(Expr::Interpolation(ast::Interpolation { parts }));
+    }
+
     fn consume(&mut self, token_type: TokenType, message: String) -> Result<&Token, ParseError> {
         if self.check_token_type(&token_type) {
             return Ok(self.advance());
@@ -200,9 +1324,23 @@ impl<'a> Parser<'a> {
         return self.error::<&Token>(self.peek().clone(), message);
     }
 
+    fn consume_identifier(&mut self, message: String) -> Result<Token, ParseError> {
+        if let TokenType::Identifier(_) = self.peek().get_token_type() {
+            return Ok(self.advance().clone());
+        }
+        return self.error::<Token>(self.peek().clone(), message);
+    }
+
+    fn lines_since(&self, start: u32) -> ast::Lines {
+        return ast::Lines {
+            start,
+            end: self.previous().get_line(),
+        };
+    }
+
     fn match_token_type(&mut self, types: &Vec<TokenType>) -> bool {
         for token_type in types {
-            if self.check_token_type(&token_type) {
+            if self.check_token_type(token_type) {
                 self.advance();
                 return true;
             }
@@ -230,15 +1368,34 @@ impl<'a> Parser<'a> {
         return &self.tokens[self.current];
     }
     fn previous(&self) -> &Token {
-        if self.current <= 0 {
+        if self.current == 0 {
             return &self.tokens[self.current];
         }
         return &self.tokens[self.current - 1];
     }
+    // One more level of nesting. Operator chains like `1 + 2 + 3` nest to the left, so every
+    // operator counts as a level too.
+    fn nest(&mut self) -> Result<(), ParseError> {
+        if self.depth >= MAX_DEPTH {
+            return self.error::<()>(self.peek().clone(), String::from("Too much nesting."));
+        }
+        self.depth += 1;
+        return Ok(());
+    }
+    fn nested<T>(&mut self, parse: Parse<'a, T>) -> Result<T, ParseError> {
+        self.nest()?;
+        let result: Result<T, ParseError> = parse(self);
+        self.depth -= 1;
+        return result;
+    }
     fn error<T>(&mut self, token: Token, msg: String) -> Result<T, ParseError> {
         utils::parse_error(&token, &msg, self.had_err);
-
-        // panic!("{}: {}", msg, token.to_string());
+        self.errors.push(SyntaxError::new(
+            token.get_line(),
+            token.get_column(),
+            token.get_lexeme().len(),
+            msg.clone(),
+        ));
 
         return Err(ParseError::Bad { msg, token });
     }
@@ -257,8 +1414,13 @@ impl<'a> Parser<'a> {
                 | TokenType::If
                 | TokenType::While
                 | TokenType::Print
-                | TokenType::Return => return,
-                _ => todo!(),
+                | TokenType::Return
+                | TokenType::Break
+                | TokenType::Continue
+                | TokenType::Throw
+                | TokenType::Try
+                | TokenType::Import => return,
+                _ => (),
             }
             self.advance();
         }
@@ -0,0 +1,150 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::interpreter::{Interpreter, LoxFunction, RuntimeError, DEFAULT_STACK_SIZE};
+use crate::lexer::{Token, TokenType};
+use crate::parser::{ast, Stmt};
+
+// How one test went.
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    name: String,
+    // the error that failed the test, as reported for scripts.
+    failure: Option<String>,
+    // what the test printed.
+    output: String,
+}
+
+impl TestResult {
+    pub fn get_name(&self) -> &str {
+        return &self.name;
+    }
+    pub fn get_failure(&self) -> Option<&str> {
+        return self.failure.as_deref();
+    }
+    pub fn get_output(&self) -> &str {
+        return &self.output;
+    }
+    pub fn passed(&self) -> bool {
+        return self.failure.is_none();
+    }
+}
+
+// Runs the tests of a parsed script: top-level functions named test_* without parameters and
+// top-level `test "name" { ... }` blocks, in source order. Each test gets a fresh interpreter
+// that first runs the script itself, so tests cannot see each other's changes.
+pub struct TestRunner {
+    statements: Vec<Stmt>,
+    // of the thread the tests run on, see Interpreter::set_stack_size.
+    stack_size: usize,
+}
+
+impl TestRunner {
+    pub fn new(statements: Vec<Stmt>) -> TestRunner {
+        return Self {
+            statements,
+            stack_size: DEFAULT_STACK_SIZE,
+        };
+    }
+    pub fn set_stack_size(&mut self, size: usize) {
+        self.stack_size = size;
+    }
+
+    // Every test of the script, as a function without parameters.
+    pub fn discover(&self) -> Vec<Rc<ast::Function>> {
+        let mut tests: Vec<Rc<ast::Function>> = Vec::new();
+
+        for statement in &self.statements {
+            match statement {
+                Stmt::Function(function)
+                    if function.name.get_lexeme().starts_with("test_")
+                        && function.params.is_empty() =>
+                {
+                    tests.push(Rc::new(function.clone()));
+                }
+                Stmt::Test(test) => {
+                    let name: String = match test.name.get_token_type() {
+                        TokenType::String(name) => name.clone(),
+                        _ => test.name.get_lexeme(),
+                    };
+                    tests.push(Rc::new(ast::Function {
+                        name: Token::new(
+                            TokenType::Identifier(name.clone()),
+                            name,
+                            test.name.get_line(),
+                            test.name.get_column(),
+                        ),
+                        params: Vec::new(),
+                        body: test.body.clone(),
+                        lines: test.lines,
+                    }));
+                }
+                _ => (),
+            }
+        }
+        return tests;
+    }
+
+    pub fn run(&self) -> Vec<TestResult> {
+        return self
+            .discover()
+            .into_iter()
+            .map(|test| self.run_test(test))
+            .collect();
+    }
+
+    fn run_test(&self, test: Rc<ast::Function>) -> TestResult {
+        let output: SharedBuffer = SharedBuffer::default();
+        let mut had_runtime_err: bool = false;
+        let mut interpreter: Interpreter = Interpreter::new(&mut had_runtime_err);
+        interpreter.set_stack_size(self.stack_size);
+
+        // What the script prints around the tests is not part of any of them.
+        interpreter.set_output(Box::new(io::sink()));
+        interpreter.interpret(self.statements.clone());
+        interpreter.set_output(Box::new(output.clone()));
+
+        let name: String = test.name.get_lexeme();
+        let failure: Option<String> = match interpreter.had_runtime_error() {
+            false => {
+                let function: LoxFunction =
+                    LoxFunction::new(test, interpreter.get_globals(), interpreter.get_file());
+                match interpreter.call_function(Rc::new(function), Vec::new()) {
+                    Ok(_) => None,
+                    Err(error) => Some(describe(&error)),
+                }
+            }
+            true => Some(String::from(
+                "Error: The script failed before the test ran.",
+            )),
+        };
+
+        return TestResult {
+            name,
+            failure,
+            output: String::from_utf8_lossy(&output.0.borrow()).into_owned(),
+        };
+    }
+}
+
+fn describe(error: &RuntimeError) -> String {
+    return match (error.get_token(), error.get_message()) {
+        (Some(token), Some(message)) => format!("[line {}] Error: {}", token.get_line(), message),
+        (None, Some(message)) => format!("Error: {}", message),
+        (_, None) => String::from("Error: The test was interrupted."),
+    };
+}
+
+// Collects what a test prints, readable after the interpreter is gone.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        return self.0.borrow_mut().write(buf);
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        return Ok(());
+    }
+}
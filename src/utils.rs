@@ -1,7 +1,46 @@
+use std::io::Write;
+
+use crate::interpreter::Frame;
 use crate::lexer::{Token, TokenType};
 
+// Frames of a stack trace that are shown, the innermost ones.
+const TRACE_LIMIT: usize = 8;
+
+// A scan or parse error, kept for tools that show errors in place (see lsp).
+#[derive(Debug, Clone)]
+pub struct SyntaxError {
+    line: u32,
+    column: u32,
+    // length in bytes of the offending source text.
+    length: usize,
+    message: String,
+}
+
+impl SyntaxError {
+    pub fn new(line: u32, column: u32, length: usize, message: String) -> SyntaxError {
+        return Self {
+            line,
+            column,
+            length,
+            message,
+        };
+    }
+    pub fn get_line(&self) -> u32 {
+        return self.line;
+    }
+    pub fn get_column(&self) -> u32 {
+        return self.column;
+    }
+    pub fn get_length(&self) -> usize {
+        return self.length;
+    }
+    pub fn get_message(&self) -> &str {
+        return &self.message;
+    }
+}
+
 pub fn report(line: u32, location: &str, message: &str, had_err: &mut bool) {
-    eprintln!("[line {}] Error {}: {}", line, location, message);
+    eprintln!("[line {}] Error{}: {}", line, location, message);
     *had_err = true;
 }
 
@@ -15,13 +54,38 @@ pub fn parse_error(token: &Token, msg: &str, had_err: &mut bool) {
     } else {
         report(
             token.get_line(),
-            &format!("at '{}'", &token.get_lexeme()),
+            &format!(" at '{}'", &token.get_lexeme()),
             msg,
             had_err,
         )
     }
 }
 
-pub fn runtime_error(token: &Token, msg: &str, had_runtime_err: &mut bool) {
-    report(token.get_line(), "", msg, had_runtime_err);
+pub fn runtime_error(output: &mut dyn Write, token: &Token, msg: &str, had_runtime_err: &mut bool) {
+    let _ = writeln!(output, "[line {}] Error: {}", token.get_line(), msg);
+    *had_runtime_err = true;
+}
+
+// Below a runtime error, the frames it was raised in, innermost first.
+pub fn stack_trace(output: &mut dyn Write, trace: &[Frame]) {
+    for frame in trace.iter().take(TRACE_LIMIT) {
+        let _ = match frame.get_file() {
+            Some(file) => writeln!(
+                output,
+                "[line {}] in {} ({})",
+                frame.get_line(),
+                frame.get_function(),
+                file
+            ),
+            None => writeln!(
+                output,
+                "[line {}] in {}",
+                frame.get_line(),
+                frame.get_function()
+            ),
+        };
+    }
+    if trace.len() > TRACE_LIMIT {
+        let _ = writeln!(output, "...");
+    }
 }
@@ -0,0 +1,72 @@
+#![allow(clippy::needless_return)]
+
+// The tree Parser builds for single expressions, as printed by AstPrinter.
+
+use rlox::lexer::Scanner;
+use rlox::parser::{AstPrinter, Expr, Parser};
+
+fn print(source: &str) -> String {
+    let mut had_err: bool = false;
+    let tokens = Scanner::new(source).scan_tokens(&mut had_err).clone();
+    let expr: Expr = Parser::new(tokens, &mut had_err)
+        .parse_expression()
+        .expect("expression should parse");
+    return AstPrinter.print(&expr);
+}
+
+#[test]
+fn conditionals_are_right_associative() {
+    assert_eq!(print("a ? b : c ? d : e"), "(?: a b (?: c d e))");
+    assert_eq!(print("a or b ? 1 : 2"), "(?: (or a b) 1 2)");
+    // The else branch may be an assignment.
+    assert_eq!(print("a ? b : c = 1"), "(?: a b (= c 1))");
+}
+
+#[test]
+fn comma_has_the_lowest_precedence() {
+    assert_eq!(print("a = 1, b = 2"), "(, (= a 1) (= b 2))");
+    assert_eq!(print("a, b, c"), "(, (, a b) c)");
+    assert_eq!(print("a ? b, c : d"), "(?: a (, b c) d)");
+    // A comma inside a call separates arguments.
+    assert_eq!(print("f(a, b)"), "(call f a b)");
+}
+
+#[test]
+fn power_and_bitwise_precedence() {
+    assert_eq!(print("-2 ** 2"), "(- (** 2 2))");
+    assert_eq!(print("2 ** 3 ** 2"), "(** 2 (** 3 2))");
+    assert_eq!(print("1 | 2 == 3"), "(== (| 1 2) 3)");
+    assert_eq!(print("x += 1"), "(+= x 1)");
+}
+
+#[test]
+fn lists_and_indexing() {
+    assert_eq!(print("[1, [2], ]"), "(list 1 (list 2))");
+    assert_eq!(print("xs[0][1]"), "(index (index xs 0) 1)");
+    assert_eq!(print("f()[0](1)"), "(call (index (call f) 0) 1)");
+    assert_eq!(print("-xs[0] ** 2"), "(- (** (index xs 0) 2))");
+    assert_eq!(print("xs[i, j] += 1"), "(+= index xs (, i j) 1)");
+}
+
+#[test]
+fn maps() {
+    assert_eq!(print("{}"), "(map)");
+    assert_eq!(print("{\"a\": 1, b: [2]}"), "(map a 1 b (list 2))");
+    assert_eq!(print("{a: b ? c : d}"), "(map a (?: b c d))");
+}
+
+#[test]
+fn method_calls() {
+    assert_eq!(print("s.upper()"), "(call (. upper s))");
+    assert_eq!(
+        print("s.split(\",\")[0].trim()"),
+        "(call (. trim (index (call (. split s) ,) 0)))"
+    );
+}
+
+#[test]
+fn ranges_bind_looser_than_arithmetic() {
+    assert_eq!(print("0..n - 1"), "(.. 0 (- n 1))");
+    assert_eq!(print("a..=b | c"), "(..= a (| b c))");
+    assert_eq!(print("0..1 == r"), "(== (.. 0 1) r)");
+}
@@ -0,0 +1,149 @@
+#![allow(clippy::needless_return)]
+
+// Runs every .lox file under tests/lox through the rlox binary and checks its behaviour against
+// the expectation comments in the file:
+//
+//     print 1 + 2; // expect: 3
+//     -"a"; // expect runtime error: Operand must be a number.
+//     print 1 +; // [line 3] Error at ';': Expected expression!
+//     // expect stderr: [line 5] in f (tests/lox/functions/f.lox)
+//
+// `expect:` lines are matched against stdout in order. A runtime error is expected on stderr for
+// the line holding the comment, `[line N] Error` and `expect stderr:` lines are expected on
+// stderr as written. The exit code must be 65 when syntax errors are expected, 70 for a runtime
+// error, 0 otherwise.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const EXPECT: &str = "// expect: ";
+const EXPECT_RUNTIME_ERROR: &str = "// expect runtime error: ";
+const EXPECT_SYNTAX_ERROR: &str = "// [line ";
+const EXPECT_STDERR: &str = "// expect stderr: ";
+
+struct Expectations {
+    stdout: Vec<String>,
+    stderr: Vec<String>,
+    exit_code: i32,
+}
+
+impl Expectations {
+    fn parse(source: &str) -> Expectations {
+        let mut stdout: Vec<String> = Vec::new();
+        let mut stderr: Vec<String> = Vec::new();
+        let mut syntax_error: bool = false;
+        let mut runtime_error: bool = false;
+
+        for (index, line) in source.lines().enumerate() {
+            if let Some(start) = line.find(EXPECT) {
+                stdout.push(String::from(&line[start + EXPECT.len()..]));
+            } else if let Some(start) = line.find(EXPECT_RUNTIME_ERROR) {
+                let message: &str = &line[start + EXPECT_RUNTIME_ERROR.len()..];
+                stderr.push(format!("[line {}] Error: {}", index + 1, message));
+                runtime_error = true;
+            } else if let Some(start) = line.find(EXPECT_STDERR) {
+                stderr.push(String::from(&line[start + EXPECT_STDERR.len()..]));
+            } else if let Some(start) = line.find(EXPECT_SYNTAX_ERROR) {
+                stderr.push(String::from(&line[start + "// ".len()..]));
+                syntax_error = true;
+            }
+        }
+
+        let exit_code: i32 = match (syntax_error, runtime_error) {
+            (true, _) => 65,
+            (false, true) => 70,
+            (false, false) => 0,
+        };
+        return Expectations {
+            stdout,
+            stderr,
+            exit_code,
+        };
+    }
+}
+
+fn collect_lox_files(directory: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(directory).unwrap() {
+        let path: PathBuf = entry.unwrap().path();
+        if path.is_dir() {
+            collect_lox_files(&path, files);
+        } else if path.extension().is_some_and(|extension| extension == "lox") {
+            files.push(path);
+        }
+    }
+}
+
+fn lines(bytes: &[u8]) -> Vec<String> {
+    return String::from_utf8_lossy(bytes)
+        .lines()
+        .map(String::from)
+        .collect();
+}
+
+// What went wrong running `path`, if anything.
+fn check(path: &Path) -> Option<String> {
+    let source: String = fs::read_to_string(path).unwrap();
+    let expected: Expectations = Expectations::parse(&source);
+
+    // Relative to the crate, as stack traces show the path.
+    let root: &Path = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let output: Output = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .current_dir(root)
+        .arg(path.strip_prefix(root).unwrap_or(path))
+        .output()
+        .unwrap();
+    let stdout: Vec<String> = lines(&output.stdout);
+    let stderr: Vec<String> = lines(&output.stderr);
+    let exit_code: i32 = output.status.code().unwrap_or(-1);
+
+    let mut problems: Vec<String> = Vec::new();
+    if stdout != expected.stdout {
+        problems.push(format!(
+            "stdout: expected {:?}, got {:?}",
+            expected.stdout, stdout
+        ));
+    }
+    if stderr != expected.stderr {
+        problems.push(format!(
+            "stderr: expected {:?}, got {:?}",
+            expected.stderr, stderr
+        ));
+    }
+    if exit_code != expected.exit_code {
+        problems.push(format!(
+            "exit code: expected {}, got {}",
+            expected.exit_code, exit_code
+        ));
+    }
+
+    if problems.is_empty() {
+        return None;
+    }
+    return Some(format!(
+        "{}\n    {}",
+        path.display(),
+        problems.join("\n    ")
+    ));
+}
+
+#[test]
+fn conformance() {
+    let mut files: Vec<PathBuf> = Vec::new();
+    collect_lox_files(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/lox"),
+        &mut files,
+    );
+    files.sort();
+    assert!(!files.is_empty(), "no .lox files under tests/lox");
+
+    let failures: Vec<String> = files.iter().filter_map(|path| check(path)).collect();
+    if !failures.is_empty() {
+        panic!(
+            "{} of {} .lox tests failed:\n{}",
+            failures.len(),
+            files.len(),
+            failures.join("\n")
+        );
+    }
+}
@@ -0,0 +1,57 @@
+#![allow(clippy::needless_return)]
+
+// Builds the concrete syntax tree of every conformance script, which must give back the source
+// byte for byte.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rlox::lexer::{Scanner, Token};
+use rlox::parser::{CstBuilder, SyntaxNode};
+
+fn collect_lox_files(directory: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(directory).unwrap() {
+        let path: PathBuf = entry.unwrap().path();
+        if path.is_dir() {
+            collect_lox_files(&path, files);
+        } else if path.extension().is_some_and(|extension| extension == "lox") {
+            files.push(path);
+        }
+    }
+}
+
+fn build(source: &str) -> (SyntaxNode, usize) {
+    let mut had_err: bool = false;
+    let tokens: Vec<Token> = Scanner::new_lossless(source)
+        .scan_tokens(&mut had_err)
+        .clone();
+    let token_count: usize = tokens.len();
+    return (CstBuilder::new(tokens).build(), token_count);
+}
+
+#[test]
+fn trees_of_the_conformance_scripts_are_lossless() {
+    let mut files: Vec<PathBuf> = Vec::new();
+    collect_lox_files(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/lox"),
+        &mut files,
+    );
+    files.sort();
+    assert!(!files.is_empty());
+
+    for file in &files {
+        let source: String = fs::read_to_string(file).unwrap();
+        let (tree, token_count) = build(&source);
+
+        assert_eq!(tree.to_source(), source, "{}", file.display());
+        assert_eq!(tree.tokens().len(), token_count, "{}", file.display());
+    }
+}
+
+#[test]
+fn bad_input_is_kept_as_trivia() {
+    let source: &str = "print \"unterminated;\n@ var x = ;";
+    let (tree, _) = build(source);
+
+    assert_eq!(tree.to_source(), source);
+}
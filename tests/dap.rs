@@ -0,0 +1,245 @@
+#![allow(clippy::needless_return)]
+
+// Drives the debug adapter in-process with recorded client sessions.
+
+use std::cell::RefCell;
+use std::io::{self, Cursor, Write};
+use std::rc::Rc;
+
+use rlox::dap::DebugAdapter;
+use rlox::lsp::transport;
+use serde_json::{json, Value};
+
+fn program() -> String {
+    return format!("{}/tests/dap/program.lox", env!("CARGO_MANIFEST_DIR"));
+}
+
+fn failing_program() -> String {
+    return format!("{}/tests/dap/error.lox", env!("CARGO_MANIFEST_DIR"));
+}
+
+// Keeps what the adapter writes readable after the adapter is gone.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        return self.0.borrow_mut().write(buf);
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        return Ok(());
+    }
+}
+
+// Sends `commands` (command, arguments) as requests numbered from 1 and returns every message
+// the adapter sent back.
+fn session(commands: Vec<(&str, Value)>) -> Vec<Value> {
+    let mut input: Vec<u8> = Vec::new();
+    for (index, (command, arguments)) in commands.into_iter().enumerate() {
+        let request: Value = json!({
+            "seq": index + 1,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        });
+        transport::write_message(&mut input, &request).unwrap();
+    }
+    return run(input);
+}
+
+// Feeds `input` to the adapter as is and returns every message it sent back.
+fn run(input: Vec<u8>) -> Vec<Value> {
+    let output: SharedBuffer = SharedBuffer::default();
+    DebugAdapter::new(Box::new(Cursor::new(input)), Box::new(output.clone()))
+        .run()
+        .unwrap();
+
+    let mut replies: Vec<Value> = Vec::new();
+    let mut reader: Cursor<Vec<u8>> = Cursor::new(output.0.borrow().clone());
+    while let Some(reply) = transport::read_message(&mut reader).unwrap() {
+        replies.push(reply);
+    }
+    return replies;
+}
+
+fn response(replies: &[Value], request_seq: u64) -> &Value {
+    return replies
+        .iter()
+        .find(|reply| reply["type"] == "response" && reply["request_seq"] == request_seq)
+        .unwrap_or_else(|| panic!("no response to request {}", request_seq));
+}
+
+fn events<'a>(replies: &'a [Value], event: &str) -> Vec<&'a Value> {
+    return replies
+        .iter()
+        .filter(|reply| reply["type"] == "event" && reply["event"] == event)
+        .collect();
+}
+
+fn launch(stop_on_entry: bool) -> Value {
+    return json!({ "program": program(), "stopOnEntry": stop_on_entry });
+}
+
+fn breakpoints(lines: &[u32]) -> Value {
+    let breakpoints: Vec<Value> = lines.iter().map(|line| json!({ "line": line })).collect();
+    return json!({ "source": { "path": program() }, "breakpoints": breakpoints });
+}
+
+#[test]
+fn breakpoint_inspect_step_and_continue() {
+    let replies: Vec<Value> = session(vec![
+        ("initialize", json!({ "adapterID": "rlox" })),
+        ("launch", launch(false)),
+        ("setBreakpoints", breakpoints(&[2])),
+        ("configurationDone", json!({})),
+        ("stackTrace", json!({ "threadId": 1 })),
+        ("scopes", json!({ "frameId": 0 })),
+        ("variables", json!({ "variablesReference": 1 })),
+        ("evaluate", json!({ "expression": "n * 10", "frameId": 0 })),
+        ("evaluate", json!({ "expression": "total", "frameId": 1 })),
+        ("evaluate", json!({ "expression": "missing", "frameId": 0 })),
+        ("next", json!({ "threadId": 1 })),
+        ("evaluate", json!({ "expression": "result", "frameId": 0 })),
+        ("setBreakpoints", breakpoints(&[])),
+        ("continue", json!({ "threadId": 1 })),
+        ("disconnect", json!({})),
+    ]);
+
+    assert_eq!(
+        response(&replies, 1)["body"]["supportsConfigurationDoneRequest"],
+        true
+    );
+    assert_eq!(events(&replies, "initialized").len(), 1);
+    assert_eq!(
+        response(&replies, 3)["body"]["breakpoints"],
+        json!([{ "verified": true, "line": 2 }])
+    );
+
+    let stopped: Vec<&Value> = events(&replies, "stopped");
+    assert_eq!(stopped.len(), 2);
+    assert_eq!(stopped[0]["body"]["reason"], "breakpoint");
+    assert_eq!(stopped[1]["body"]["reason"], "step");
+
+    let frames: &Value = &response(&replies, 5)["body"]["stackFrames"];
+    assert_eq!(frames.as_array().unwrap().len(), 2);
+    assert_eq!(frames[0]["name"], "square");
+    assert_eq!(frames[0]["line"], 2);
+    assert_eq!(frames[0]["source"]["name"], "program.lox");
+    assert_eq!(frames[1]["name"], "<script>");
+    assert_eq!(frames[1]["line"], 8);
+
+    let scopes: &Value = &response(&replies, 6)["body"]["scopes"];
+    assert_eq!(scopes[0]["name"], "Locals");
+    assert_eq!(scopes[0]["variablesReference"], 1);
+    assert_eq!(scopes[1]["name"], "Globals");
+
+    assert_eq!(
+        response(&replies, 7)["body"]["variables"],
+        json!([{ "name": "n", "value": "1", "type": "integer", "variablesReference": 0 }])
+    );
+    assert_eq!(response(&replies, 8)["body"]["result"], "10");
+    assert_eq!(response(&replies, 9)["body"]["result"], "0");
+    assert_eq!(response(&replies, 10)["success"], false);
+    assert_eq!(
+        response(&replies, 10)["message"],
+        "Undefined variable 'missing'."
+    );
+    assert_eq!(response(&replies, 12)["body"]["result"], "1");
+
+    let output: Vec<&Value> = events(&replies, "output");
+    assert_eq!(output.len(), 1);
+    assert_eq!(output[0]["body"]["output"], "14\n");
+    assert_eq!(events(&replies, "exited")[0]["body"]["exitCode"], 0);
+    assert_eq!(events(&replies, "terminated").len(), 1);
+    assert_eq!(response(&replies, 15)["success"], true);
+
+    // Sequence numbers of the adapter's messages count up from 1.
+    for (index, reply) in replies.iter().enumerate() {
+        assert_eq!(reply["seq"], index as u64 + 1);
+    }
+}
+
+#[test]
+fn stop_on_entry_step_in_and_terminate() {
+    let replies: Vec<Value> = session(vec![
+        ("initialize", json!({ "adapterID": "rlox" })),
+        ("launch", launch(true)),
+        ("configurationDone", json!({})),
+        ("stepIn", json!({ "threadId": 1 })),
+        ("stackTrace", json!({ "threadId": 1 })),
+        ("terminate", json!({})),
+        ("disconnect", json!({})),
+    ]);
+
+    let stopped: Vec<&Value> = events(&replies, "stopped");
+    assert_eq!(stopped.len(), 2);
+    assert_eq!(stopped[0]["body"]["reason"], "entry");
+    assert_eq!(stopped[1]["body"]["reason"], "step");
+
+    let frames: &Value = &response(&replies, 5)["body"]["stackFrames"];
+    assert_eq!(frames.as_array().unwrap().len(), 1);
+    assert_eq!(frames[0]["line"], 6);
+
+    // Terminated before the print.
+    assert!(events(&replies, "output").is_empty());
+    assert_eq!(events(&replies, "terminated").len(), 1);
+    assert_eq!(response(&replies, 7)["success"], true);
+}
+
+#[test]
+fn requests_about_a_program_need_it_paused() {
+    let replies: Vec<Value> = session(vec![
+        ("initialize", json!({ "adapterID": "rlox" })),
+        ("stackTrace", json!({ "threadId": 1 })),
+        ("launch", json!({ "program": "does/not/exist.lox" })),
+    ]);
+
+    assert_eq!(response(&replies, 2)["success"], false);
+    assert_eq!(response(&replies, 3)["success"], false);
+}
+
+#[test]
+fn runtime_errors_are_output_events() {
+    let replies: Vec<Value> = session(vec![
+        ("initialize", json!({ "adapterID": "rlox" })),
+        ("launch", json!({ "program": failing_program() })),
+        ("configurationDone", Value::Null),
+    ]);
+
+    let output: Vec<(&Value, &Value)> = events(&replies, "output")
+        .iter()
+        .map(|event| (&event["body"]["category"], &event["body"]["output"]))
+        .collect();
+    assert_eq!(
+        output,
+        vec![
+            (&json!("stdout"), &json!("before\n")),
+            (
+                &json!("stderr"),
+                &json!("[line 2] Error: Operand must be a number.\n")
+            ),
+            (
+                &json!("stderr"),
+                &json!(format!("[line 2] in fail ({})\n", failing_program()))
+            ),
+            (
+                &json!("stderr"),
+                &json!(format!("[line 6] in <script> ({})\n", failing_program()))
+            ),
+        ]
+    );
+    assert_eq!(events(&replies, "exited")[0]["body"]["exitCode"], 70);
+}
+
+#[test]
+fn unparseable_requests_get_an_error_response() {
+    let mut input: Vec<u8> = Vec::new();
+    input.extend_from_slice(b"Content-Length: 5\r\n\r\n{bad}");
+    let request: Value = json!({ "seq": 1, "type": "request", "command": "initialize" });
+    transport::write_message(&mut input, &request).unwrap();
+    let replies: Vec<Value> = run(input);
+
+    assert_eq!(replies[0]["type"], "response");
+    assert_eq!(replies[0]["success"], false);
+    assert_eq!(response(&replies, 1)["success"], true);
+}
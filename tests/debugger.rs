@@ -0,0 +1,106 @@
+#![allow(clippy::needless_return)]
+
+// Runs `rlox debug` on tests/debugger/program.lox with commands on stdin.
+
+use std::io::Write;
+use std::process::{Child, Command, Output, Stdio};
+
+fn debug(commands: &str) -> (String, i32) {
+    let mut child: Child = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .args(["debug", "tests/debugger/program.lox"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(commands.as_bytes())
+        .unwrap();
+
+    let output: Output = child.wait_with_output().unwrap();
+    return (
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        output.status.code().unwrap_or(-1),
+    );
+}
+
+#[test]
+fn breakpoints_stepping_and_inspection() {
+    let commands: &str = "\
+break 2
+break 10
+delete 10
+delete 4
+continue
+locals
+backtrace
+print x + 1
+next
+print result
+step
+step
+next
+
+print total
+locals
+bogus
+quit
+";
+
+    assert_eq!(
+        debug(commands),
+        (
+            String::from(
+                "\
+<script> at line 1
+    1 | fun square(x) {
+(debug) Breakpoint at line 2.
+(debug) Breakpoint at line 10.
+(debug) Deleted breakpoint at line 10.
+(debug) No breakpoint at line 4.
+(debug) square at line 2
+    2 | var result = x * x;
+(debug) x = 1
+(debug) #0 square at line 2
+#1 <script> at line 8
+(debug) 2
+(debug) square at line 3
+    3 | return result;
+(debug) 1
+(debug) <script> at line 8
+    8 | total = total + square(i);
+(debug) square at line 2
+    2 | var result = x * x;
+(debug) square at line 3
+    3 | return result;
+(debug) <script> at line 8
+    8 | total = total + square(i);
+(debug) 5
+(debug) i = 3
+(debug) Unknown command 'bogus', try 'help'.
+(debug) "
+            ),
+            0
+        )
+    );
+}
+
+#[test]
+fn continue_runs_to_the_end_without_breakpoints() {
+    assert_eq!(
+        debug("continue\n"),
+        (
+            String::from(
+                "\
+<script> at line 1
+    1 | fun square(x) {
+(debug) 14
+"
+            ),
+            0
+        )
+    );
+}
@@ -0,0 +1,135 @@
+#![allow(clippy::needless_return)]
+
+// Formats Lox source with Formatter, and through `rlox fmt`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use rlox::lexer::{Scanner, Token};
+use rlox::parser::{Formatter, Parser, Stmt};
+
+// None when the source has syntax errors.
+fn format(source: &str) -> Option<String> {
+    let mut had_err: bool = false;
+    let mut scanner: Scanner = Scanner::new(source);
+    let tokens: Vec<Token> = scanner.scan_tokens(&mut had_err).clone();
+    let statements: Vec<Stmt> = Parser::new(tokens, &mut had_err).parse();
+    if had_err {
+        return None;
+    }
+    return Some(Formatter::new(scanner.get_comments()).format(&statements));
+}
+
+fn collect_lox_files(directory: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(directory).unwrap() {
+        let path: PathBuf = entry.unwrap().path();
+        if path.is_dir() {
+            collect_lox_files(&path, files);
+        } else if path.extension().is_some_and(|extension| extension == "lox") {
+            files.push(path);
+        }
+    }
+}
+
+fn rlox_fmt(args: &[&str]) -> (String, i32) {
+    let output: Output = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .arg("fmt")
+        .args(args)
+        .output()
+        .unwrap();
+    return (
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        output.status.code().unwrap_or(-1),
+    );
+}
+
+// A fresh directory for the files a test formats in place.
+fn scratch_directory(name: &str) -> PathBuf {
+    let directory: PathBuf =
+        std::env::temp_dir().join(format!("rlox-fmt-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    return directory;
+}
+
+#[test]
+fn formatting_is_idempotent_on_the_conformance_scripts() {
+    let mut files: Vec<PathBuf> = Vec::new();
+    collect_lox_files(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/lox"),
+        &mut files,
+    );
+    files.sort();
+
+    let mut changed: Vec<String> = Vec::new();
+    for file in &files {
+        let Some(formatted) = format(&fs::read_to_string(file).unwrap()) else {
+            continue;
+        };
+        if format(&formatted).as_ref() != Some(&formatted) {
+            changed.push(file.display().to_string());
+        }
+    }
+    assert_eq!(changed, Vec::<String>::new());
+}
+
+#[test]
+fn trailing_comments_stay_after_the_last_statement_of_their_line() {
+    assert_eq!(
+        format("fun f() { f(); } f(); // c\n{ g(); } // d\nvar x = 1; // e\n"),
+        Some(String::from(
+            "\
+fun f() {
+    f();
+}
+f(); // c
+{
+    g();
+} // d
+var x = 1; // e
+"
+        ))
+    );
+}
+
+#[test]
+fn fmt_rewrites_files_and_check_lists_unformatted_ones() {
+    let directory: PathBuf = scratch_directory("check");
+    let file: PathBuf = directory.join("messy.lox");
+    fs::write(&file, "var   a=1 ;print a+ 2;").unwrap();
+    let file_name: &str = file.to_str().unwrap();
+
+    // --check leaves the file alone.
+    assert_eq!(
+        rlox_fmt(&["--check", directory.to_str().unwrap()]),
+        (format!("{}\n", file_name), 1)
+    );
+    assert_eq!(fs::read_to_string(&file).unwrap(), "var   a=1 ;print a+ 2;");
+
+    assert_eq!(rlox_fmt(&[file_name]), (String::new(), 0));
+    assert_eq!(
+        fs::read_to_string(&file).unwrap(),
+        "var a = 1;\nprint a + 2;\n"
+    );
+    assert_eq!(rlox_fmt(&["--check", file_name]), (String::new(), 0));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn fmt_leaves_files_with_syntax_errors_alone() {
+    let directory: PathBuf = scratch_directory("errors");
+    let file: PathBuf = directory.join("broken.lox");
+    fs::write(&file, "print 1 +;").unwrap();
+
+    assert_eq!(rlox_fmt(&[file.to_str().unwrap()]), (String::new(), 65));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "print 1 +;");
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn fmt_reports_missing_files() {
+    assert_eq!(rlox_fmt(&["tests/no_such_file.lox"]), (String::new(), 66));
+}
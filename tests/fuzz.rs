@@ -0,0 +1,118 @@
+#![allow(clippy::needless_return)]
+
+// Inputs that crashed the fuzz targets under fuzz/, each run through the same stages as the
+// target that found it.
+
+use std::io;
+use std::thread;
+
+use rlox::interpreter::Interpreter;
+use rlox::lexer::{Scanner, Token};
+use rlox::parser::{Parser, Stmt};
+use rlox::utils::SyntaxError;
+
+// What the rlox binary runs on, see main.rs. Test threads get much less.
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+fn on_main_sized_stack(test: fn()) {
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(test)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+fn parse_errors(source: &str) -> Vec<String> {
+    let mut had_err: bool = false;
+    let tokens: Vec<Token> = Scanner::new(source).scan_tokens(&mut had_err).clone();
+    let mut parser: Parser = Parser::new(tokens, &mut had_err);
+    parser.parse();
+    return parser
+        .get_errors()
+        .iter()
+        .map(|error: &SyntaxError| String::from(error.get_message()))
+        .collect();
+}
+
+// Whether running `source` raised a runtime error.
+fn run(source: &str) -> bool {
+    let mut had_err: bool = false;
+    let tokens: Vec<Token> = Scanner::new(source).scan_tokens(&mut had_err).clone();
+    let statements: Vec<Stmt> = Parser::new(tokens, &mut had_err).parse();
+    assert!(!had_err);
+
+    let mut had_runtime_err: bool = false;
+    let mut interpreter: Interpreter = Interpreter::new(&mut had_runtime_err);
+    interpreter.set_output(Box::new(io::sink()));
+    interpreter.interpret(statements);
+    drop(interpreter);
+    return had_runtime_err;
+}
+
+#[test]
+fn unbounded_recursion() {
+    on_main_sized_stack(|| {
+        assert!(run("fun f() { f(); }\nf();"));
+        assert!(run("fun f(n) { return ((((f(n + 1))))); }\nf(0);"));
+    });
+}
+
+#[test]
+fn deep_nesting() {
+    on_main_sized_stack(|| {
+        let sources: Vec<String> = vec![
+            format!("print {}1{};", "(".repeat(10_000), ")".repeat(10_000)),
+            format!("print {}1;", "-".repeat(10_000)),
+            format!("var a; {};", "a = ".repeat(10_000)),
+            format!("{}{}", "{".repeat(10_000), "}".repeat(10_000)),
+            format!("{}print 1;", "if (true) ".repeat(10_000)),
+            format!("fun f() {{ return f; }}\nf{};", "()".repeat(10_000)),
+        ];
+        for source in sources {
+            assert!(parse_errors(&source).contains(&String::from("Too much nesting.")));
+        }
+    });
+}
+
+#[test]
+fn long_operator_chains() {
+    on_main_sized_stack(|| {
+        for operator in ["+", "*", "==", "<", "and", "or"] {
+            let source: String = format!("print 1{};", format!(" {} 1", operator).repeat(10_000));
+            assert!(parse_errors(&source).contains(&String::from("Too much nesting.")));
+        }
+    });
+}
+
+#[test]
+fn nesting_below_the_limit() {
+    on_main_sized_stack(|| {
+        let source: String = format!("print {}1{};", "(".repeat(200), ")".repeat(200));
+        assert!(parse_errors(&source).is_empty());
+        assert!(!run(&source));
+    });
+}
+
+#[test]
+fn non_ascii_input() {
+    for source in [
+        "print \"é\";",
+        "var é = 1;",
+        "// ü\nprint 1;",
+        "\"ß",
+        "€€",
+        "1é2",
+    ] {
+        let mut had_err: bool = false;
+        Scanner::new(source).scan_tokens(&mut had_err);
+
+        let mut scanner: Scanner = Scanner::new_lossless(source);
+        let text: String = scanner
+            .scan_tokens(&mut had_err)
+            .iter()
+            .map(|token| token.to_source())
+            .collect();
+        assert_eq!(text, source);
+    }
+}
@@ -0,0 +1,206 @@
+#![allow(clippy::needless_return)]
+
+// What Interpreter tells a host embedding it about the errors of a script.
+
+use std::io;
+use std::path::PathBuf;
+
+use rlox::interpreter::{Exception, Interpreter, DEFAULT_STACK_SIZE};
+use rlox::lexer::{Scanner, Token};
+use rlox::parser::{Parser, Stmt};
+
+// (function, file, line)
+type TraceFrame = (String, Option<String>, u32);
+
+// (message, trace) of every uncaught error.
+fn errors(source: &str, file: Option<&str>) -> Vec<(String, Vec<TraceFrame>)> {
+    return errors_with_search_paths(source, file, vec![]);
+}
+
+fn errors_with_search_paths(
+    source: &str,
+    file: Option<&str>,
+    search_paths: Vec<PathBuf>,
+) -> Vec<(String, Vec<TraceFrame>)> {
+    let mut had_err: bool = false;
+    let tokens: Vec<Token> = Scanner::new(source).scan_tokens(&mut had_err).clone();
+    let statements: Vec<Stmt> = Parser::new(tokens, &mut had_err).parse();
+    assert!(!had_err);
+
+    let mut had_runtime_err: bool = false;
+    let mut interpreter: Interpreter = Interpreter::new(&mut had_runtime_err);
+    interpreter.set_output(Box::new(io::sink()));
+    interpreter.set_error_output(Box::new(io::sink()));
+    if let Some(file) = file {
+        interpreter.set_file(file);
+    }
+    interpreter.set_search_paths(search_paths);
+    interpreter.interpret(statements);
+
+    return interpreter
+        .get_errors()
+        .iter()
+        .map(|error: &Exception| {
+            let trace = error
+                .get_trace()
+                .iter()
+                .map(|frame| {
+                    (
+                        String::from(frame.get_function()),
+                        frame.get_file().map(String::from),
+                        frame.get_line(),
+                    )
+                })
+                .collect();
+            (String::from(error.get_message()), trace)
+        })
+        .collect();
+}
+
+fn frame(function: &str, file: Option<&str>, line: u32) -> TraceFrame {
+    return (String::from(function), file.map(String::from), line);
+}
+
+#[test]
+fn uncaught_errors_have_a_stack_trace_innermost_first() {
+    let source: &str = "\
+fun inner() {
+    return nil + 1;
+}
+fun outer() {
+    inner();
+}
+outer();";
+
+    assert_eq!(
+        errors(source, Some("main.lox")),
+        vec![(
+            String::from("Operands must be two number or two strings."),
+            vec![
+                frame("inner", Some("main.lox"), 2),
+                frame("outer", Some("main.lox"), 5),
+                frame("<script>", Some("main.lox"), 7),
+            ]
+        )]
+    );
+}
+
+#[test]
+fn each_failing_statement_is_an_error() {
+    assert_eq!(
+        errors("print -nil;\nprint 1;\nthrow \"done\";", None),
+        vec![
+            (
+                String::from("Operand must be a number."),
+                vec![frame("<script>", None, 1)]
+            ),
+            (String::from("done"), vec![frame("<script>", None, 3)]),
+        ]
+    );
+}
+
+#[test]
+fn caught_errors_are_not_reported() {
+    assert_eq!(
+        errors("try { throw 1; } catch (e) {}", Some("main.lox")),
+        vec![]
+    );
+}
+
+#[test]
+fn errors_of_native_functions_are_raised_at_the_call() {
+    let source: &str = "\
+fun fails() { throw \"inner\"; }
+assert_error(fails, \"other\");";
+
+    assert_eq!(
+        errors(source, Some("main.lox")),
+        vec![(
+            String::from("Expected error 'other' but got 'inner'."),
+            vec![frame("<script>", Some("main.lox"), 2)]
+        )]
+    );
+}
+
+#[test]
+fn errors_in_modules_are_traced_to_their_file() {
+    let source: &str = "\
+import \"../modules/failing.lox\" as failing;
+failing.check(-1);";
+
+    assert_eq!(
+        errors(source, Some("tests/lox/main.lox")),
+        vec![(
+            String::from("Negative value."),
+            vec![
+                frame("check", Some("tests/modules/failing.lox"), 2),
+                frame("<script>", Some("tests/lox/main.lox"), 2),
+            ]
+        )]
+    );
+}
+
+#[test]
+fn modules_with_syntax_errors_are_not_run() {
+    assert_eq!(
+        errors(
+            "import \"../modules/syntax_error.lox\" as broken;",
+            Some("tests/lox/main.lox")
+        ),
+        vec![(
+            String::from("Module 'tests/modules/syntax_error.lox' has syntax errors."),
+            vec![frame("<script>", Some("tests/lox/main.lox"), 1)]
+        )]
+    );
+}
+
+#[test]
+fn modules_are_searched_after_the_importing_directory() {
+    let source: &str = "\
+import { square } from \"math.lox\";
+import \"shadow.lox\" as shadow;
+if (square(2) != 4 or shadow.pi != \"shadow\") throw \"wrong module\";";
+
+    assert_eq!(
+        errors(source, Some("tests/lox/main.lox")),
+        vec![
+            (
+                String::from("Cannot find module 'math.lox'."),
+                vec![frame("<script>", Some("tests/lox/main.lox"), 1)]
+            ),
+            (
+                String::from("Cannot find module 'shadow.lox'."),
+                vec![frame("<script>", Some("tests/lox/main.lox"), 2)]
+            ),
+            (
+                String::from("Undefined variable 'square'."),
+                vec![frame("<script>", Some("tests/lox/main.lox"), 3)]
+            ),
+        ]
+    );
+    assert_eq!(
+        errors_with_search_paths(
+            source,
+            Some("tests/lox/main.lox"),
+            vec![PathBuf::from("tests/modules")]
+        ),
+        vec![]
+    );
+}
+
+#[test]
+fn runaway_recursion_fails_within_a_spawned_thread() {
+    let messages: Vec<String> = std::thread::Builder::new()
+        .stack_size(DEFAULT_STACK_SIZE)
+        .spawn(|| {
+            errors("fun f() { f(); } f();", None)
+                .into_iter()
+                .map(|(message, _)| message)
+                .collect()
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert_eq!(messages, vec![String::from("Stack overflow.")]);
+}
@@ -0,0 +1,285 @@
+#![allow(clippy::needless_return)]
+
+// Drives the language server in-process with a scripted client session.
+
+use std::io::Cursor;
+
+use rlox::lsp::{transport, LanguageServer};
+use serde_json::{json, Value};
+
+const URI: &str = "file:///test.lox";
+
+const SOURCE: &str = "// globals
+var greeting = \"hi\";
+var answer = 40 + 2;
+{
+    var answer = 1;
+    print answer;
+}
+print greeting;
+";
+
+fn session(messages: Vec<Value>) -> (i32, Vec<Value>) {
+    let mut input: Vec<u8> = Vec::new();
+    for message in &messages {
+        transport::write_message(&mut input, message).unwrap();
+    }
+
+    let mut output: Vec<u8> = Vec::new();
+    let exit_code: i32 = LanguageServer::new()
+        .run(&mut Cursor::new(input), &mut output)
+        .unwrap();
+
+    let mut replies: Vec<Value> = Vec::new();
+    let mut reader: Cursor<Vec<u8>> = Cursor::new(output);
+    while let Some(reply) = transport::read_message(&mut reader).unwrap() {
+        replies.push(reply);
+    }
+    return (exit_code, replies);
+}
+
+fn request(id: u64, method: &str, params: Value) -> Value {
+    return json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    return json!({ "jsonrpc": "2.0", "method": method, "params": params });
+}
+
+fn open(text: &str) -> Value {
+    return notification(
+        "textDocument/didOpen",
+        json!({ "textDocument": { "uri": URI, "languageId": "lox", "version": 1, "text": text } }),
+    );
+}
+
+fn at(line: u32, character: u32) -> Value {
+    return json!({ "textDocument": { "uri": URI }, "position": { "line": line, "character": character } });
+}
+
+fn range(start: (u32, u32), end: (u32, u32)) -> Value {
+    return json!({
+        "start": { "line": start.0, "character": start.1 },
+        "end": { "line": end.0, "character": end.1 },
+    });
+}
+
+fn response(replies: &[Value], id: u64) -> &Value {
+    return &replies
+        .iter()
+        .find(|reply| reply["id"] == json!(id))
+        .expect("no response")["result"];
+}
+
+#[test]
+fn initialize_and_shutdown() {
+    let (exit_code, replies) = session(vec![
+        request(1, "initialize", json!({ "capabilities": {} })),
+        notification("initialized", json!({})),
+        request(2, "shutdown", Value::Null),
+        notification("exit", Value::Null),
+    ]);
+
+    let capabilities: &Value = &response(&replies, 1)["capabilities"];
+    assert_eq!(capabilities["hoverProvider"], json!(true));
+    assert_eq!(capabilities["definitionProvider"], json!(true));
+    assert_eq!(capabilities["documentSymbolProvider"], json!(true));
+    assert_eq!(
+        capabilities["semanticTokensProvider"]["legend"]["tokenTypes"][0],
+        json!("keyword")
+    );
+    assert_eq!(response(&replies, 2), &Value::Null);
+    assert_eq!(exit_code, 0);
+}
+
+#[test]
+fn exit_without_shutdown_fails() {
+    let (exit_code, _) = session(vec![notification("exit", Value::Null)]);
+    assert_eq!(exit_code, 1);
+}
+
+#[test]
+fn malformed_messages_get_a_parse_error() {
+    let mut input: Vec<u8> = Vec::new();
+    input.extend_from_slice(b"Content-Length: 8\r\n\r\n{\"id\": 1");
+    transport::write_message(&mut input, &request(2, "shutdown", Value::Null)).unwrap();
+    transport::write_message(&mut input, &notification("exit", Value::Null)).unwrap();
+
+    let mut output: Vec<u8> = Vec::new();
+    let exit_code: i32 = LanguageServer::new()
+        .run(&mut Cursor::new(input), &mut output)
+        .unwrap();
+    let mut reader: Cursor<Vec<u8>> = Cursor::new(output);
+    let error: Value = transport::read_message(&mut reader).unwrap().unwrap();
+    let shutdown: Value = transport::read_message(&mut reader).unwrap().unwrap();
+
+    assert_eq!(error["id"], Value::Null);
+    assert_eq!(error["error"]["code"], json!(-32700));
+    assert_eq!(shutdown["id"], json!(2));
+    assert_eq!(shutdown["result"], Value::Null);
+    assert_eq!(exit_code, 0);
+}
+
+#[test]
+fn frames_without_a_length_are_skipped() {
+    let mut input: Vec<u8> = Vec::new();
+    input.extend_from_slice(b"Foo: bar\r\n\r\n");
+    transport::write_message(&mut input, &request(1, "shutdown", Value::Null)).unwrap();
+    transport::write_message(&mut input, &notification("exit", Value::Null)).unwrap();
+
+    let mut output: Vec<u8> = Vec::new();
+    let exit_code: i32 = LanguageServer::new()
+        .run(&mut Cursor::new(input), &mut output)
+        .unwrap();
+    let mut reader: Cursor<Vec<u8>> = Cursor::new(output);
+    let shutdown: Value = transport::read_message(&mut reader).unwrap().unwrap();
+
+    assert_eq!(shutdown["id"], json!(1));
+    assert_eq!(exit_code, 0);
+}
+
+#[test]
+fn truncated_input_ends_the_session() {
+    for input in [
+        &b"Foo: bar\r\n\r\n{}"[..],
+        &b"Content-Length: 999\r\n\r\n{\"id\": 1}"[..],
+    ] {
+        let mut output: Vec<u8> = Vec::new();
+        let exit_code: i32 = LanguageServer::new()
+            .run(&mut Cursor::new(input), &mut output)
+            .unwrap();
+
+        assert_eq!(output, Vec::<u8>::new());
+        assert_eq!(exit_code, 1);
+    }
+}
+
+#[test]
+fn publishes_diagnostics() {
+    let (_, replies) = session(vec![
+        open("print 1 +;\nvar = 2;\n@"),
+        notification(
+            "textDocument/didChange",
+            json!({ "textDocument": { "uri": URI, "version": 2 }, "contentChanges": [{ "text": "print 1;" }] }),
+        ),
+    ]);
+
+    assert_eq!(
+        replies[0]["method"],
+        json!("textDocument/publishDiagnostics")
+    );
+    let diagnostics: &Vec<Value> = replies[0]["params"]["diagnostics"].as_array().unwrap();
+    let messages: Vec<&str> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic["message"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            "unexpected character.",
+            "Expected expression!",
+            "Expect variable name."
+        ]
+    );
+    assert_eq!(diagnostics[0]["range"], range((2, 0), (2, 1)));
+    assert_eq!(diagnostics[2]["range"], range((1, 4), (1, 5)));
+
+    assert_eq!(replies[1]["params"]["diagnostics"], json!([]));
+}
+
+#[test]
+fn hover_shows_literal_types() {
+    let (_, replies) = session(vec![
+        open(SOURCE),
+        request(1, "textDocument/hover", at(1, 17)),
+        request(2, "textDocument/hover", at(2, 16)),
+        request(3, "textDocument/hover", at(5, 10)),
+    ]);
+
+    assert_eq!(
+        response(&replies, 1)["contents"]["value"],
+        json!("string: \"hi\"")
+    );
+    assert_eq!(response(&replies, 1)["range"], range((1, 15), (1, 19)));
+    // On the operator of a constant expression the whole expression is evaluated.
+    assert_eq!(
+        response(&replies, 2)["contents"]["value"],
+        json!("integer: 42")
+    );
+    assert_eq!(response(&replies, 3), &Value::Null);
+}
+
+#[test]
+fn document_symbols_list_declarations() {
+    let (_, replies) = session(vec![
+        open(SOURCE),
+        request(
+            1,
+            "textDocument/documentSymbol",
+            json!({ "textDocument": { "uri": URI } }),
+        ),
+    ]);
+
+    let symbols: &Value = response(&replies, 1);
+    assert_eq!(symbols[0]["name"], json!("greeting"));
+    assert_eq!(symbols[0]["selectionRange"], range((1, 4), (1, 12)));
+    assert_eq!(symbols[1]["name"], json!("answer"));
+    assert_eq!(symbols[1]["range"], range((2, 0), (2, 20)));
+    assert_eq!(symbols.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn goes_to_the_declaration_in_scope() {
+    let (_, replies) = session(vec![
+        open(SOURCE),
+        request(1, "textDocument/definition", at(5, 12)),
+        request(2, "textDocument/definition", at(7, 8)),
+        request(3, "textDocument/definition", at(0, 3)),
+    ]);
+
+    assert_eq!(response(&replies, 1)["range"], range((4, 8), (4, 14)));
+    assert_eq!(response(&replies, 1)["uri"], json!(URI));
+    assert_eq!(response(&replies, 2)["range"], range((1, 4), (1, 12)));
+    assert_eq!(response(&replies, 3), &Value::Null);
+}
+
+#[test]
+fn positions_count_utf16_units_past_non_ascii_text() {
+    let (_, replies) = session(vec![
+        open("var été = \"🦀\"; print été;\n"),
+        request(1, "textDocument/definition", at(0, 23)),
+        request(2, "textDocument/hover", at(0, 11)),
+    ]);
+
+    assert_eq!(response(&replies, 1)["range"], range((0, 4), (0, 7)));
+    assert_eq!(response(&replies, 2)["range"], range((0, 10), (0, 14)));
+}
+
+#[test]
+fn semantic_tokens_cover_comments_and_tokens() {
+    let (_, replies) = session(vec![
+        open("// hi\nvar x = \"a\nb\";"),
+        request(
+            1,
+            "textDocument/semanticTokens/full",
+            json!({ "textDocument": { "uri": URI } }),
+        ),
+    ]);
+
+    #[rustfmt::skip]
+    let expected: Vec<u32> = vec![
+        0, 0, 5, 5, 0, // comment
+        1, 0, 3, 0, 0, // var
+        0, 4, 1, 3, 0, // x
+        0, 2, 1, 4, 0, // =
+        0, 2, 2, 1, 0, // "a
+        1, 0, 2, 1, 0, // b"
+    ];
+    assert_eq!(response(&replies, 1)["data"], json!(expected));
+}
+
+#[test]
+fn unknown_requests_are_errors() {
+    let (_, replies) = session(vec![request(1, "textDocument/rename", json!({}))]);
+    assert_eq!(replies[0]["error"]["code"], json!(-32601));
+}
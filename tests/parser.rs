@@ -0,0 +1,123 @@
+#![allow(clippy::needless_return)]
+
+// Syntax errors reported by Parser.
+
+use rlox::lexer::Scanner;
+use rlox::parser::{Parser, Stmt};
+
+// (message, line, column) of every parse error.
+fn errors(source: &str) -> Vec<(String, u32, u32)> {
+    let mut had_err: bool = false;
+    let tokens = Scanner::new(source).scan_tokens(&mut had_err).clone();
+    let mut parser: Parser = Parser::new(tokens, &mut had_err);
+    parser.parse();
+    return parser
+        .get_errors()
+        .iter()
+        .map(|error| {
+            (
+                String::from(error.get_message()),
+                error.get_line(),
+                error.get_column(),
+            )
+        })
+        .collect();
+}
+
+#[test]
+fn binary_operators_without_a_left_operand() {
+    let operators: [&str; 18] = [
+        "or", "and", "==", "!=", ">", ">=", "<", "<=", "|", "^", "&", "<<", ">>", "+", "*", "/",
+        "%", "**",
+    ];
+
+    for operator in operators {
+        assert_eq!(
+            errors(&format!("print {} 3;", operator)),
+            vec![(
+                format!("Binary operator '{}' requires a left operand.", operator),
+                1,
+                7
+            )],
+            "for {}",
+            operator
+        );
+    }
+}
+
+#[test]
+fn minus_is_still_unary() {
+    assert_eq!(errors("print - 3;"), vec![]);
+}
+
+#[test]
+fn parsing_goes_on_after_a_missing_left_operand() {
+    // The right operand is parsed at the operator's precedence, so the rest of the expression
+    // and the statements after it are checked too.
+    assert_eq!(
+        errors("* 3 + 4;\nprint == 1 < 2;\nprint 1 +;"),
+        vec![
+            (
+                String::from("Binary operator '*' requires a left operand."),
+                1,
+                1
+            ),
+            (
+                String::from("Binary operator '==' requires a left operand."),
+                2,
+                7
+            ),
+            (String::from("Expected expression!"), 3, 10),
+        ]
+    );
+
+    let mut had_err: bool = false;
+    let tokens = Scanner::new("== 4; print 5;")
+        .scan_tokens(&mut had_err)
+        .clone();
+    let statements: Vec<Stmt> = Parser::new(tokens, &mut had_err).parse();
+    assert_eq!(statements.len(), 2);
+    assert!(had_err);
+}
+
+#[test]
+fn unclosed_brackets() {
+    assert_eq!(
+        errors("xs[0;"),
+        vec![(String::from("Expect ']' after index."), 1, 5)]
+    );
+    assert_eq!(
+        errors("[1, 2;"),
+        vec![(String::from("Expect ']' after list elements."), 1, 6)]
+    );
+}
+
+#[test]
+fn braces_start_a_map_only_before_a_key_and_a_colon() {
+    assert_eq!(errors("{\"a\": 1};"), vec![]);
+    assert_eq!(errors("{a: 1}[\"a\"] = 2;"), vec![]);
+    assert_eq!(errors("{ a; }"), vec![]);
+    assert_eq!(errors("{}"), vec![]);
+    // Parenthesized, any key will do.
+    assert_eq!(errors("({-1: 2});"), vec![]);
+    // Unless a loop follows, which makes it a label.
+    assert_eq!(errors("{ a: while (true) break a; }"), vec![]);
+    assert_eq!(errors("{a: for (x in 0..1) continue a; }"), vec![]);
+}
+
+#[test]
+fn jumps_need_an_enclosing_loop() {
+    assert_eq!(errors("while (true) { if (true) break; }"), vec![]);
+    assert_eq!(
+        errors("for (;;) {} continue;"),
+        vec![(
+            String::from("Can't use 'continue' outside of a loop."),
+            1,
+            13
+        )]
+    );
+    assert_eq!(
+        errors("a: while (true) { b: while (true) {} break b; }"),
+        vec![(String::from("No enclosing loop labeled 'b'."), 1, 44)]
+    );
+}
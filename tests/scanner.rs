@@ -0,0 +1,391 @@
+#![allow(clippy::needless_return)]
+
+// Tokens and lexer errors produced by Scanner.
+
+use rlox::lexer::{Scanner, Token, TokenType, TriviaKind};
+
+fn scan(source: &str) -> Vec<Token> {
+    let mut had_err: bool = false;
+    return Scanner::new(source).scan_tokens(&mut had_err).clone();
+}
+
+// (message, line, column) of every lexer error.
+fn errors(source: &str) -> Vec<(String, u32, u32)> {
+    let mut had_err: bool = false;
+    let mut scanner: Scanner = Scanner::new(source);
+    scanner.scan_tokens(&mut had_err);
+    return scanner
+        .get_errors()
+        .iter()
+        .map(|error| {
+            (
+                String::from(error.get_message()),
+                error.get_line(),
+                error.get_column(),
+            )
+        })
+        .collect();
+}
+
+#[test]
+fn unicode_identifiers() {
+    let tokens: Vec<Token> = scan("var π = 3; ñandú_2 = größe;");
+    let identifiers: Vec<&TokenType> = tokens
+        .iter()
+        .map(|token| token.get_token_type())
+        .filter(|token_type| matches!(token_type, TokenType::Identifier(_)))
+        .collect();
+
+    assert_eq!(
+        identifiers,
+        vec![
+            &TokenType::Identifier(String::from("π")),
+            &TokenType::Identifier(String::from("ñandú_2")),
+            &TokenType::Identifier(String::from("größe")),
+        ]
+    );
+}
+
+#[test]
+fn strings_keep_non_ascii_text() {
+    let tokens: Vec<Token> = scan("\"héllo ✓ 日本 🦀\"");
+    assert_eq!(
+        tokens[0].get_token_type(),
+        &TokenType::String(String::from("héllo ✓ 日本 🦀"))
+    );
+}
+
+#[test]
+fn columns_count_characters() {
+    let tokens: Vec<Token> = scan("\"日本\" + été;\n  🦀");
+    let columns: Vec<(String, u32, u32)> = tokens
+        .iter()
+        .map(|token| (token.get_lexeme(), token.get_line(), token.get_column()))
+        .collect();
+
+    assert_eq!(
+        columns,
+        vec![
+            (String::from("\"日本\""), 1, 1),
+            (String::from("+"), 1, 6),
+            (String::from("été"), 1, 8),
+            (String::from(";"), 1, 11),
+            // not an identifier
+            (String::new(), 2, 4),
+        ]
+    );
+    assert_eq!(
+        errors("\"日本\" + été;\n  🦀"),
+        vec![(String::from("unexpected character."), 2, 3)]
+    );
+}
+
+#[test]
+fn columns_follow_strings_across_lines() {
+    let tokens: Vec<Token> = scan("r#\"a\"# x \"b\nc\" y r\"\n\" z");
+    let columns: Vec<(u32, u32)> = tokens
+        .iter()
+        .map(|token| (token.get_line(), token.get_column()))
+        .collect();
+
+    assert_eq!(
+        columns,
+        vec![(1, 1), (1, 8), (1, 10), (2, 4), (2, 6), (3, 3), (3, 4)]
+    );
+}
+
+#[test]
+fn escapes() {
+    let tokens: Vec<Token> = scan(r#""a\nb\tc\\d\"e\u{e9}\u{1F980}""#);
+    assert_eq!(
+        tokens[0].get_token_type(),
+        &TokenType::String(String::from("a\nb\tc\\d\"eé🦀"))
+    );
+    // The lexeme is the literal as written.
+    assert_eq!(tokens[0].get_lexeme(), r#""a\nb\tc\\d\"e\u{e9}\u{1F980}""#);
+}
+
+#[test]
+fn invalid_escapes_are_reported_where_they_are() {
+    assert_eq!(
+        errors("var s = \"ok\\q\";\nprint \"日本\\u{D800}\\u{}\\u12\";"),
+        vec![
+            (String::from("Invalid escape sequence '\\q'."), 1, 12),
+            (String::from("Invalid unicode escape '\\u{D800}'."), 2, 10),
+            (String::from("Invalid unicode escape '\\u{}'."), 2, 18),
+            (String::from("Invalid unicode escape '\\u'."), 2, 22),
+        ]
+    );
+    // The string is still a token, so the parser does not report it again.
+    assert!(matches!(
+        scan("\"\\q\"")[0].get_token_type(),
+        TokenType::String(_)
+    ));
+}
+
+#[test]
+fn raw_strings() {
+    let tokens: Vec<Token> = scan("r\"C:\\dir\\n\" r#\"say \"hi\" \\d+\"# r\"\" r\"#;\"");
+    let strings: Vec<&TokenType> = tokens.iter().map(|token| token.get_token_type()).collect();
+
+    assert_eq!(
+        strings,
+        vec![
+            &TokenType::String(String::from("C:\\dir\\n")),
+            &TokenType::String(String::from("say \"hi\" \\d+")),
+            &TokenType::String(String::new()),
+            &TokenType::String(String::from("#;")),
+            &TokenType::Eof,
+        ]
+    );
+    assert_eq!(
+        errors("r#\"never closed\""),
+        vec![(String::from("Unterminated string."), 1, 1)]
+    );
+    // Not followed by a string, r is a name.
+    assert_eq!(
+        scan("r #")[0].get_token_type(),
+        &TokenType::Identifier(String::from("r"))
+    );
+}
+
+#[test]
+fn interpolations() {
+    let tokens: Vec<Token> = scan("\"a ${b} c ${ {} } ${\"d${e}\"}\"");
+    let token_types: Vec<&TokenType> = tokens.iter().map(|token| token.get_token_type()).collect();
+
+    assert_eq!(
+        token_types,
+        vec![
+            &TokenType::Interpolation(String::from("a ")),
+            &TokenType::Identifier(String::from("b")),
+            &TokenType::Interpolation(String::from(" c ")),
+            // Braces inside the expression do not end it.
+            &TokenType::LeftBrace,
+            &TokenType::RightBrace,
+            &TokenType::Interpolation(String::from(" ")),
+            &TokenType::Interpolation(String::from("d")),
+            &TokenType::Identifier(String::from("e")),
+            &TokenType::String(String::new()),
+            &TokenType::String(String::new()),
+            &TokenType::Eof,
+        ]
+    );
+    // The segments keep their delimiters, so the source is covered.
+    assert_eq!(tokens[2].get_lexeme(), "} c ${");
+    assert_eq!(
+        scan("\"\\${a}\"")[0].get_token_type(),
+        &TokenType::String(String::from("${a}"))
+    );
+}
+
+#[test]
+fn number_literals() {
+    let tokens: Vec<Token> = scan("0xFF 0b1010 0o17 1.5e-3 2E3 1_000_000 0xdead_BEEF 7.0");
+    let numbers: Vec<&TokenType> = tokens.iter().map(|token| token.get_token_type()).collect();
+
+    assert_eq!(
+        numbers,
+        vec![
+            &TokenType::Integer(255),
+            &TokenType::Integer(10),
+            &TokenType::Integer(15),
+            &TokenType::Number(1.5e-3),
+            &TokenType::Number(2000.0),
+            &TokenType::Integer(1_000_000),
+            &TokenType::Integer(0xdead_beef),
+            &TokenType::Number(7.0),
+            &TokenType::Eof,
+        ]
+    );
+    assert_eq!(tokens[0].get_lexeme(), "0xFF");
+}
+
+#[test]
+fn malformed_numbers_are_reported() {
+    assert_eq!(
+        errors("0x; 0b; 0o;\n1e; 1e+;\n0b102 0o8 0xFG;\n1_ 1__0 1.5_ 1e_5 0x_1;"),
+        vec![
+            (String::from("Expect hexadecimal digits after '0x'."), 1, 1),
+            (String::from("Expect binary digits after '0b'."), 1, 5),
+            (String::from("Expect octal digits after '0o'."), 1, 9),
+            (String::from("Expect digits after the exponent."), 2, 1),
+            (String::from("Expect digits after the exponent."), 2, 5),
+            (String::from("Invalid digit '2' in binary number."), 3, 1),
+            (String::from("Invalid digit '8' in octal number."), 3, 7),
+            (
+                String::from("Invalid digit 'G' in hexadecimal number."),
+                3,
+                11
+            ),
+            (
+                String::from("Digit separators must be between digits."),
+                4,
+                1
+            ),
+            (
+                String::from("Digit separators must be between digits."),
+                4,
+                4
+            ),
+            (
+                String::from("Digit separators must be between digits."),
+                4,
+                9
+            ),
+            (
+                String::from("Digit separators must be between digits."),
+                4,
+                14
+            ),
+            (
+                String::from("Digit separators must be between digits."),
+                4,
+                19
+            ),
+        ]
+    );
+    // Still a number, so the parser does not report it again.
+    assert_eq!(scan("0x")[0].get_token_type(), &TokenType::Number(0.0));
+}
+
+#[test]
+fn block_comments_nest_and_count_lines() {
+    let tokens: Vec<Token> = scan("a /* one\n/* two\n*/ still */ b\n/**/c");
+    let lines: Vec<(String, u32, u32)> = tokens
+        .iter()
+        .map(|token| (token.get_lexeme(), token.get_line(), token.get_column()))
+        .collect();
+
+    assert_eq!(
+        lines,
+        vec![
+            (String::from("a"), 1, 1),
+            (String::from("b"), 3, 13),
+            (String::from("c"), 4, 5),
+            (String::new(), 4, 6),
+        ]
+    );
+}
+
+#[test]
+fn unterminated_block_comments_are_reported_where_they_open() {
+    assert_eq!(
+        errors("print 1;\n  /* a /* b */\nprint 2;"),
+        vec![(String::from("Unterminated block comment."), 2, 3)]
+    );
+}
+
+#[test]
+fn doc_comments_are_kept_as_trivia() {
+    let mut had_err: bool = false;
+    let mut scanner: Scanner = Scanner::new_lossless("/// Adds.\n// plain\n//// plain too\nfun");
+    let tokens: Vec<Token> = scanner.scan_tokens(&mut had_err).clone();
+    let comments: Vec<(TriviaKind, &str)> = tokens[0]
+        .get_leading_trivia()
+        .iter()
+        .filter(|trivia| trivia.get_kind() != TriviaKind::Newline)
+        .map(|trivia| (trivia.get_kind(), trivia.get_text()))
+        .collect();
+
+    assert_eq!(
+        comments,
+        vec![
+            (TriviaKind::DocComment, "/// Adds."),
+            (TriviaKind::Comment, "// plain"),
+            (TriviaKind::Comment, "//// plain too"),
+        ]
+    );
+}
+
+#[test]
+fn integer_literals_must_fit_in_64_bits() {
+    assert_eq!(
+        scan("9223372036854775807")[0].get_token_type(),
+        &TokenType::Integer(i64::MAX)
+    );
+    assert_eq!(
+        errors("9223372036854775808 0x8000_0000_0000_0000"),
+        vec![
+            (String::from("Integer literal is too large."), 1, 1),
+            (String::from("Integer literal is too large."), 1, 21),
+        ]
+    );
+}
+
+#[test]
+fn operators() {
+    let tokens: Vec<Token> = scan("% ** & | ^ ~ << >> += -= *= /= %= <= >= * /");
+    let token_types: Vec<&TokenType> = tokens.iter().map(|token| token.get_token_type()).collect();
+
+    assert_eq!(
+        token_types,
+        vec![
+            &TokenType::Percent,
+            &TokenType::StarStar,
+            &TokenType::Ampersand,
+            &TokenType::Pipe,
+            &TokenType::Caret,
+            &TokenType::Tilde,
+            &TokenType::LessLess,
+            &TokenType::GreaterGreater,
+            &TokenType::PlusEqual,
+            &TokenType::MinusEqual,
+            &TokenType::StarEqual,
+            &TokenType::SlashEqual,
+            &TokenType::PercentEqual,
+            &TokenType::LessEqual,
+            &TokenType::GreaterEqual,
+            &TokenType::Star,
+            &TokenType::Slash,
+            &TokenType::Eof,
+        ]
+    );
+}
+
+#[test]
+fn brackets() {
+    let tokens: Vec<Token> = scan("xs[0] = [];");
+    let token_types: Vec<&TokenType> = tokens.iter().map(|token| token.get_token_type()).collect();
+
+    assert_eq!(
+        token_types,
+        vec![
+            &TokenType::Identifier(String::from("xs")),
+            &TokenType::LeftBracket,
+            &TokenType::Integer(0),
+            &TokenType::RightBracket,
+            &TokenType::Equal,
+            &TokenType::LeftBracket,
+            &TokenType::RightBracket,
+            &TokenType::Semicolon,
+            &TokenType::Eof,
+        ]
+    );
+}
+
+#[test]
+fn ranges_are_not_fractions() {
+    let tokens: Vec<Token> = scan("for (i in 0..=1.5..2) a.b");
+    let token_types: Vec<&TokenType> = tokens.iter().map(|token| token.get_token_type()).collect();
+
+    assert_eq!(
+        token_types,
+        vec![
+            &TokenType::For,
+            &TokenType::LeftParen,
+            &TokenType::Identifier(String::from("i")),
+            &TokenType::In,
+            &TokenType::Integer(0),
+            &TokenType::DotDotEqual,
+            &TokenType::Number(1.5),
+            &TokenType::DotDot,
+            &TokenType::Integer(2),
+            &TokenType::RightParen,
+            &TokenType::Identifier(String::from("a")),
+            &TokenType::Dot,
+            &TokenType::Identifier(String::from("b")),
+            &TokenType::Eof,
+        ]
+    );
+}
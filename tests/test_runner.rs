@@ -0,0 +1,78 @@
+#![allow(clippy::needless_return)]
+
+// Runs `rlox test` on the scripts under tests/testing.
+
+use std::process::{Command, Output};
+
+fn rlox_test(paths: &[&str]) -> (String, i32) {
+    let output: Output = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .arg("test")
+        .args(paths)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .unwrap();
+    return (
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        output.status.code().unwrap_or(-1),
+    );
+}
+
+#[test]
+fn passing_tests() {
+    let (stdout, exit_code) = rlox_test(&["tests/testing/passing.lox"]);
+
+    assert_eq!(
+        stdout,
+        "\
+test tests/testing/passing.lox: test_add ... ok
+test tests/testing/passing.lox: test_changes_are_not_shared ... ok
+test tests/testing/passing.lox: changes are not shared ... ok
+test tests/testing/passing.lox: assert_error ... ok
+
+test result: ok. 4 passed; 0 failed
+"
+    );
+    assert_eq!(exit_code, 0);
+}
+
+#[test]
+fn failing_tests() {
+    let (stdout, exit_code) = rlox_test(&["tests/testing/failing.lox"]);
+
+    assert_eq!(
+        stdout,
+        "\
+test tests/testing/failing.lox: passes ... ok
+test tests/testing/failing.lox: assert ... FAILED
+test tests/testing/failing.lox: test_assert_eq ... FAILED
+test tests/testing/failing.lox: assert_error without an error ... FAILED
+test tests/testing/failing.lox: runtime error ... FAILED
+
+failures:
+
+---- tests/testing/failing.lox: assert ----
+printed by the test
+[line 9] Error: Assertion failed.
+
+---- tests/testing/failing.lox: test_assert_eq ----
+[line 13] Error: Expected 1 but got \"1\".
+
+---- tests/testing/failing.lox: assert_error without an error ----
+[line 18] Error: Expected error 'Boom.' but none was raised.
+
+---- tests/testing/failing.lox: runtime error ----
+[line 22] Error: Operand must be a number.
+
+test result: FAILED. 1 passed; 4 failed
+"
+    );
+    assert_eq!(exit_code, 1);
+}
+
+#[test]
+fn directories_are_searched() {
+    let (stdout, exit_code) = rlox_test(&["tests/testing"]);
+
+    assert!(stdout.ends_with("test result: FAILED. 5 passed; 4 failed\n"));
+    assert_eq!(exit_code, 1);
+}